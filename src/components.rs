@@ -11,6 +11,8 @@ use ggez::graphics::Color;
 #[derive(Component)]
 pub struct Renderable {
     pub color: Color,
+    /// Lower orders are drawn on top (e.g. 0 for actors, 2 for items on the floor, 3 for corpses)
+    pub render_order: i32,
 }
 
 #[derive(Component, Debug)]
@@ -47,7 +49,665 @@ pub struct WantsToMelee {
     pub target: Entity,
 }
 
+#[derive(Component, Debug, Clone)]
+pub struct WantsToShoot {
+    pub target: Entity,
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum DamageType {
+    Slashing,
+    Piercing,
+    Bludgeoning,
+    Fire,
+    Poison,
+    Magic,
+    Starvation,
+}
+
+impl DamageType {
+    pub fn name(self) -> &'static str {
+        match self {
+            DamageType::Slashing => "slashing",
+            DamageType::Piercing => "piercing",
+            DamageType::Bludgeoning => "bludgeoning",
+            DamageType::Fire => "fire",
+            DamageType::Poison => "poison",
+            DamageType::Magic => "magic",
+            DamageType::Starvation => "starvation",
+        }
+    }
+
+    /// The inverse of `name`, for damage types read from data files
+    pub fn from_name(name: &str) -> Option<DamageType> {
+        match name {
+            "slashing" => Some(DamageType::Slashing),
+            "piercing" => Some(DamageType::Piercing),
+            "bludgeoning" => Some(DamageType::Bludgeoning),
+            "fire" => Some(DamageType::Fire),
+            "poison" => Some(DamageType::Poison),
+            "magic" => Some(DamageType::Magic),
+            "starvation" => Some(DamageType::Starvation),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DamageInstance {
+    pub amount: i32,
+    pub kind: DamageType,
+    pub source: Option<Entity>,
+}
+
+/// Every hit an entity takes this turn, applied in order by the DamageSystem
 #[derive(Component, Debug)]
 pub struct SufferDamage {
-    pub amount: i32,
+    pub instances: Vec<DamageInstance>,
+}
+
+impl SufferDamage {
+    /// Adds a hit to `victim`, on top of anything else they're suffering this turn
+    pub fn new_damage(
+        store: &mut WriteStorage<SufferDamage>,
+        victim: Entity,
+        amount: i32,
+        kind: DamageType,
+        source: Option<Entity>,
+    ) {
+        let instance = DamageInstance {
+            amount,
+            kind,
+            source,
+        };
+
+        if let Some(suffering) = store.get_mut(victim) {
+            suffering.instances.push(instance);
+        } else {
+            store
+                .insert(
+                    victim,
+                    SufferDamage {
+                        instances: vec![instance],
+                    },
+                )
+                .expect("Unable to insert SufferDamage comp");
+        }
+    }
+}
+
+/// Takes half damage of these types
+#[derive(Component, Debug)]
+pub struct Resistant {
+    pub kinds: Vec<DamageType>,
+}
+
+/// Takes double damage of these types
+#[derive(Component, Debug)]
+pub struct Vulnerable {
+    pub kinds: Vec<DamageType>,
+}
+
+/// Whoever dealt the killing blow, if anyone. Added when hp first drops below 1.
+#[derive(Component, Debug)]
+pub struct KilledBy {
+    pub killer: Option<Entity>,
+}
+
+impl CombatStats {
+    /// Heals by `amount`, never going over `max_hp`
+    pub fn heal(&mut self, amount: i32) {
+        self.hp = i32::min(self.max_hp, self.hp + amount);
+    }
+}
+
+/// Heals 1 hp every `turns_per_hp` turns, as long as no enemies are in view
+#[derive(Component, Debug)]
+pub struct HealthRegen {
+    pub turns_per_hp: i32,
+    pub counter: i32,
+}
+
+/// The player keeps passing turns until healed or interrupted
+#[derive(Component, Debug)]
+pub struct Resting {
+    /// Hit points at the last rested turn, so resting stops as soon as they go down
+    pub last_hp: i32,
+}
+
+#[derive(Component, Debug)]
+pub struct Item {}
+
+#[derive(Component, Debug, Clone)]
+pub struct InBackpack {
+    pub owner: Entity,
+}
+
+#[derive(Component, Debug, Clone)]
+pub struct WantsToPickupItem {
+    pub collected_by: Entity,
+    pub item: Entity,
+}
+
+#[derive(Component, Debug, Clone)]
+pub struct WantsToDropItem {
+    pub item: Entity,
+}
+
+/// Destroyed once used
+#[derive(Component, Debug)]
+pub struct Consumable {}
+
+#[derive(Component, Debug)]
+pub struct ProvidesHealing {
+    pub heal_amount: i32,
+}
+
+#[derive(Component, Debug, Clone)]
+pub struct WantsToUseItem {
+    pub item: Entity,
+    pub target: Option<rltk::Point>,
+}
+
+/// Item needs a target tile within `range`, picked in targeting mode
+#[derive(Component, Debug)]
+pub struct Ranged {
+    pub range: i32,
+}
+
+#[derive(Component, Debug)]
+pub struct InflictsDamage {
+    pub damage: i32,
+    pub kind: DamageType,
+}
+
+/// Effect hits everything within `radius` of the target that the blast can reach
+#[derive(Component, Debug)]
+pub struct AreaOfEffect {
+    pub radius: i32,
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum EquipmentSlot {
+    Melee,
+    Shield,
+    Armor,
+}
+
+#[derive(Component, Debug, Clone)]
+pub struct Equippable {
+    pub slot: EquipmentSlot,
+}
+
+#[derive(Component, Debug, Clone)]
+pub struct Equipped {
+    pub owner: Entity,
+    pub slot: EquipmentSlot,
+}
+
+#[derive(Component, Debug)]
+pub struct MeleePowerBonus {
+    pub power: i32,
+}
+
+#[derive(Component, Debug)]
+pub struct DefenseBonus {
+    pub defense: i32,
+}
+
+#[derive(Component, Debug, Clone)]
+pub struct WantsToRemoveItem {
+    pub item: Entity,
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum StatusEffectKind {
+    /// Moves in random directions
+    Confusion,
+    /// Takes `damage` every turn
+    Poison { damage: i32 },
+    /// Loses its turns
+    Stun,
+    /// Builds up energy twice as fast
+    Haste,
+}
+
+impl StatusEffectKind {
+    /// How the log describes someone under this effect
+    pub fn adjective(self) -> &'static str {
+        match self {
+            StatusEffectKind::Confusion => "confused",
+            StatusEffectKind::Poison { .. } => "poisoned",
+            StatusEffectKind::Stun => "stunned",
+            StatusEffectKind::Haste => "hasted",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StatusEffect {
+    pub kind: StatusEffectKind,
+    pub turns: i32,
+    /// Whoever applied the effect, credited with any damage it does
+    pub source: Option<Entity>,
+}
+
+/// Every effect currently active on an entity, ticked down once per turn
+#[derive(Component, Debug, Default)]
+pub struct StatusEffects {
+    pub effects: Vec<StatusEffect>,
+}
+
+impl StatusEffects {
+    /// Adds an effect. Re-applying an effect that's already active only refreshes its duration.
+    /// Returns true if the effect is new.
+    pub fn add(&mut self, kind: StatusEffectKind, turns: i32, source: Option<Entity>) -> bool {
+        let existing = self
+            .effects
+            .iter_mut()
+            .find(|e| std::mem::discriminant(&e.kind) == std::mem::discriminant(&kind));
+
+        match existing {
+            Some(effect) => {
+                effect.kind = kind;
+                effect.turns = i32::max(effect.turns, turns);
+                effect.source = source;
+                false
+            }
+            None => {
+                self.effects.push(StatusEffect {
+                    kind,
+                    turns,
+                    source,
+                });
+                true
+            }
+        }
+    }
+
+    pub fn is_confused(&self) -> bool {
+        self.effects
+            .iter()
+            .any(|e| e.kind == StatusEffectKind::Confusion)
+    }
+
+    pub fn is_stunned(&self) -> bool {
+        self.effects
+            .iter()
+            .any(|e| e.kind == StatusEffectKind::Stun)
+    }
+
+    /// True if any effect hurts every turn
+    pub fn is_poisoned(&self) -> bool {
+        self.effects
+            .iter()
+            .any(|e| matches!(e.kind, StatusEffectKind::Poison { .. }))
+    }
+
+    pub fn is_hasted(&self) -> bool {
+        self.effects
+            .iter()
+            .any(|e| e.kind == StatusEffectKind::Haste)
+    }
+}
+
+/// Using this item puts a status effect on its target(s)
+#[derive(Component, Debug)]
+pub struct InflictsStatus {
+    pub kind: StatusEffectKind,
+    pub turns: i32,
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum HungerState {
+    WellFed,
+    Normal,
+    Hungry,
+    Starving,
+}
+
+/// Counts down each player turn, moving to the next hunger state when it runs out
+#[derive(Component, Debug)]
+pub struct HungerClock {
+    pub state: HungerState,
+    pub duration: i32,
+}
+
+/// Eating this resets the eater's hunger clock
+#[derive(Component, Debug)]
+pub struct ProvidesFood {}
+
+/// Earned by killing things. Reaching `xp_to_next_level` raises `level`.
+#[derive(Component, Debug)]
+pub struct Experience {
+    pub level: i32,
+    pub xp: i32,
+}
+
+impl Experience {
+    pub fn xp_to_next_level(&self) -> i32 {
+        self.level * 100
+    }
+}
+
+/// XP awarded to whoever deals the killing blow
+#[derive(Component, Debug)]
+pub struct GrantsXp {
+    pub xp: i32,
+}
+
+/// Core stats. 10 is average; every 2 points above or below it is worth +/-1.
+#[derive(Component, Debug, Clone)]
+pub struct Attributes {
+    /// Hits harder in melee
+    pub might: i32,
+    /// More hp
+    pub fitness: i32,
+    /// Harder to hit
+    pub quickness: i32,
+    /// Stronger magic
+    pub intelligence: i32,
+}
+
+impl Attributes {
+    pub fn bonus(value: i32) -> i32 {
+        (value - 10).div_euclid(2)
+    }
+
+    pub fn might_bonus(&self) -> i32 {
+        Attributes::bonus(self.might)
+    }
+
+    pub fn fitness_bonus(&self) -> i32 {
+        Attributes::bonus(self.fitness)
+    }
+
+    pub fn quickness_bonus(&self) -> i32 {
+        Attributes::bonus(self.quickness)
+    }
+
+    pub fn intelligence_bonus(&self) -> i32 {
+        Attributes::bonus(self.intelligence)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Skill {
+    pub level: i32,
+    pub practice: i32,
+}
+
+impl Skill {
+    pub fn new(level: i32) -> Skill {
+        Skill { level, practice: 0 }
+    }
+
+    /// Counts one use of the skill. Returns true if that was enough to raise its level.
+    pub fn practice(&mut self) -> bool {
+        self.practice += 1;
+        if self.practice >= (self.level + 1) * 10 {
+            self.practice = 0;
+            self.level += 1;
+            return true;
+        }
+        false
+    }
+}
+
+/// Skills get better the more they're used
+#[derive(Component, Debug, Clone)]
+pub struct Skills {
+    pub melee: Skill,
+    pub ranged: Skill,
+    pub defense: Skill,
+    pub magic: Skill,
+}
+
+/// Melee damage dice, `n_dice`d`die_type`. On a creature it's their natural attack,
+/// on an equipped weapon it replaces that.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct MeleeDamage {
+    pub n_dice: i32,
+    pub die_type: i32,
+    pub kind: DamageType,
+}
+
+/// Left behind when something dies. Just scenery.
+#[derive(Component, Debug)]
+pub struct Corpse {}
+
+/// Spawns an item on the map at the given position
+pub type ItemSpawner = fn(&mut World, i32, i32);
+
+#[derive(Clone)]
+pub struct LootEntry {
+    pub weight: i32,
+    pub spawn: ItemSpawner,
+}
+
+/// What a monster might drop when it dies. `drop_chance` is a percentage.
+#[derive(Component)]
+pub struct LootTable {
+    pub drop_chance: i32,
+    pub entries: Vec<LootEntry>,
+}
+
+impl LootTable {
+    /// Picks what gets dropped, if anything, weighting each entry by its `weight`
+    pub fn roll(&self, rng: &mut rltk::RandomNumberGenerator) -> Option<ItemSpawner> {
+        let total_weight: i32 = self.entries.iter().map(|e| e.weight).sum();
+        if total_weight < 1 || rng.roll_dice(1, 100) > self.drop_chance {
+            return None;
+        }
+
+        let mut roll = rng.roll_dice(1, total_weight);
+        for entry in self.entries.iter() {
+            if roll <= entry.weight {
+                return Some(entry.spawn);
+            }
+            roll -= entry.weight;
+        }
+
+        None
+    }
+}
+
+/// Builds up by `speed` every tick of the clock. Acting spends it, and
+/// slow actions can push it below zero so it takes longer to act again.
+#[derive(Component, Debug)]
+pub struct Energy {
+    pub speed: i32,
+    pub current: i32,
+}
+
+/// Marks whoever gets to act on this tick
+#[derive(Component, Debug)]
+pub struct MyTurn {}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum AiState {
+    /// Nothing to do, so roam around
+    Wander,
+    /// Can see an enemy and is going for them
+    Chase { target: Entity },
+    /// Lost sight of its enemy, heading to where they were last seen
+    Search { target: rltk::Point },
+    /// Hurt badly or scared, running away from whatever is at `from`
+    Flee { from: rltk::Point },
+    /// Sticking close to the player
+    Follow,
+    /// Can't see anyone, but following the player's scent
+    Track,
+}
+
+impl AiState {
+    /// Shown in the debug view
+    pub fn label(self) -> &'static str {
+        match self {
+            AiState::Wander => "wander",
+            AiState::Chase { .. } => "chase",
+            AiState::Search { .. } => "search",
+            AiState::Flee { .. } => "flee",
+            AiState::Follow => "follow",
+            AiState::Track => "track",
+        }
+    }
+}
+
+/// What a monster is up to. It flees once its hp drops below `flee_below` percent.
+#[derive(Component, Debug)]
+pub struct Brain {
+    pub state: AiState,
+    pub flee_below: i32,
+    /// Where this monster last saw the enemy it was chasing
+    pub last_seen: Option<rltk::Point>,
+}
+
+/// Which side an entity is on. See `FactionTable` for how factions treat each other.
+#[derive(Component, Debug)]
+pub struct Faction {
+    pub name: String,
+}
+
+/// An ally that sticks to the player, never straying further than `leash` tiles
+#[derive(Component, Debug)]
+pub struct Follower {
+    pub leash: i32,
+}
+
+/// Something loud happened where this entity is standing. Carries for `volume` tiles.
+#[derive(Component, Debug)]
+pub struct Noise {
+    pub volume: i32,
+}
+
+/// Doesn't act until woken by noise, by getting hurt, or by spotting the player
+#[derive(Component, Debug)]
+pub struct Asleep {
+    /// Whether the player was in view last turn, so sneaking past is only rolled
+    /// when they first show up
+    pub saw_player: bool,
+}
+
+/// Hunts by smell, following the player's scent when they're out of sight
+#[derive(Component, Debug)]
+pub struct Tracker {}
+
+/// Belongs to a group of monsters that hunt together, see `Packs`
+#[derive(Component, Debug)]
+pub struct PackMember {
+    pub pack: usize,
+    /// Lost heart when the leader died, and runs from any enemy it sees
+    pub routed: bool,
+}
+
+/// Attacks from a distance, backing off when enemies come closer than `keep_away`.
+/// Loaded from the ranged attacks file, see `RangedAttackTable`.
+#[derive(Component, Debug, Clone)]
+pub struct RangedAttack {
+    pub range: i32,
+    pub keep_away: i32,
+    pub n_dice: i32,
+    pub die_type: i32,
+    pub kind: DamageType,
+    /// How the log describes the attack, e.g. "shoots"
+    pub verb: String,
+    /// What flies at the target, e.g. "an arrow"
+    pub projectile: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spawn_potion(ecs: &mut World, _x: i32, _y: i32) {
+        ecs.create_entity()
+            .with(Name {
+                name: "Potion".to_string(),
+            })
+            .build();
+    }
+
+    fn spawn_scroll(ecs: &mut World, _x: i32, _y: i32) {
+        ecs.create_entity()
+            .with(Name {
+                name: "Scroll".to_string(),
+            })
+            .build();
+    }
+
+    /// Runs `spawn` in a fresh world and returns the name of what it made
+    fn spawned_name(spawn: ItemSpawner) -> String {
+        let mut ecs = World::new();
+        ecs.register::<Name>();
+        spawn(&mut ecs, 0, 0);
+        let names = ecs.read_storage::<Name>();
+        let name = names.join().next().expect("Nothing was spawned");
+        name.name.clone()
+    }
+
+    fn table(drop_chance: i32, weights: &[(i32, ItemSpawner)]) -> LootTable {
+        LootTable {
+            drop_chance,
+            entries: weights
+                .iter()
+                .map(|(weight, spawn)| LootEntry {
+                    weight: *weight,
+                    spawn: *spawn,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn never_drops_with_no_chance() {
+        let mut rng = rltk::RandomNumberGenerator::seeded(1);
+        let loot = table(0, &[(1, spawn_potion)]);
+
+        for _ in 0..100 {
+            assert!(loot.roll(&mut rng).is_none());
+        }
+    }
+
+    #[test]
+    fn always_drops_with_full_chance() {
+        let mut rng = rltk::RandomNumberGenerator::seeded(2);
+        let loot = table(100, &[(1, spawn_potion)]);
+
+        for _ in 0..100 {
+            let spawn = loot.roll(&mut rng).expect("Nothing dropped");
+            assert_eq!(spawned_name(spawn), "Potion");
+        }
+    }
+
+    #[test]
+    fn drops_nothing_without_entries() {
+        let mut rng = rltk::RandomNumberGenerator::seeded(3);
+        let loot = table(100, &[]);
+
+        assert!(loot.roll(&mut rng).is_none());
+    }
+
+    #[test]
+    fn never_picks_entries_without_weight() {
+        let mut rng = rltk::RandomNumberGenerator::seeded(4);
+        let loot = table(100, &[(0, spawn_potion), (3, spawn_scroll)]);
+
+        for _ in 0..100 {
+            let spawn = loot.roll(&mut rng).expect("Nothing dropped");
+            assert_eq!(spawned_name(spawn), "Scroll");
+        }
+    }
+
+    #[test]
+    fn picks_every_weighted_entry() {
+        let mut rng = rltk::RandomNumberGenerator::seeded(5);
+        let loot = table(100, &[(1, spawn_potion), (1, spawn_scroll)]);
+
+        let names: Vec<String> = (0..100)
+            .map(|_| spawned_name(loot.roll(&mut rng).expect("Nothing dropped")))
+            .collect();
+        assert!(names.iter().any(|n| n == "Potion"));
+        assert!(names.iter().any(|n| n == "Scroll"));
+    }
 }
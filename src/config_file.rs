@@ -0,0 +1,132 @@
+use ggez::{Context, GameError, GameResult};
+
+use std::io::Read;
+
+/// Reads a whole file from the ggez resource dirs
+pub fn read(ctx: &mut Context, path: &str) -> GameResult<String> {
+    let mut file = ggez::filesystem::open(ctx, path)?;
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)?;
+    Ok(contents)
+}
+
+/// One line of a config file, split in two at its first separator
+pub struct ConfigLine<'a> {
+    path: &'a str,
+    /// Counting from 1, like editors do
+    pub line_no: usize,
+    pub head: &'a str,
+    pub rest: &'a str,
+}
+
+impl<'a> ConfigLine<'a> {
+    /// An error pointing at this line of the file
+    pub fn error(&self, message: String) -> GameError {
+        GameError::ConfigError(format!("{}:{}: {}", self.path, self.line_no, message))
+    }
+}
+
+/// Splits the lines of `path` at their first `separator`, with both halves trimmed.
+/// Empty lines and lines starting with `#` are skipped. Lines missing the separator,
+/// or with nothing in front of it, are an error saying what was `expected`.
+pub fn lines<'a>(
+    path: &'a str,
+    contents: &'a str,
+    separator: char,
+    expected: &str,
+) -> GameResult<Vec<ConfigLine<'a>>> {
+    let mut lines = Vec::new();
+
+    for (line_no, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut parts = line.splitn(2, separator);
+        let head = parts.next().unwrap_or("").trim();
+        let rest = parts.next();
+        let config_line = ConfigLine {
+            path,
+            line_no: line_no + 1,
+            head,
+            rest: rest.unwrap_or("").trim(),
+        };
+        if head.is_empty() || rest.is_none() {
+            return Err(config_line.error(format!("expected `{}`", expected)));
+        }
+        lines.push(config_line);
+    }
+
+    Ok(lines)
+}
+
+/// The non-empty items of a comma separated list, trimmed
+pub fn list(text: &str) -> impl Iterator<Item = &str> {
+    text.split(',')
+        .map(|item| item.trim())
+        .filter(|item| !item.is_empty())
+}
+
+/// Splits `key = value` into its trimmed halves. The value is empty if there's no `=`.
+pub fn key_value(item: &str) -> (&str, &str) {
+    let mut parts = item.splitn(2, '=');
+    let key = parts.next().unwrap_or("").trim();
+    let value = parts.next().unwrap_or("").trim();
+    (key, value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_comments_and_blank_lines() {
+        let contents = "# a comment\n\n   \nrest = R\n  # indented comment\n";
+        let lines = lines("/test.cfg", contents, '=', "action = Key").unwrap();
+
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].line_no, 4);
+        assert_eq!((lines[0].head, lines[0].rest), ("rest", "R"));
+    }
+
+    #[test]
+    fn splits_at_the_first_separator_only() {
+        let lines = lines("/test.cfg", "bow: verb = shoots: twice", ':', "name: ...").unwrap();
+
+        assert_eq!(
+            (lines[0].head, lines[0].rest),
+            ("bow", "verb = shoots: twice")
+        );
+    }
+
+    #[test]
+    fn rejects_lines_without_a_head_or_separator() {
+        assert!(lines("/test.cfg", "bow range = 6", ':', "name: ...").is_err());
+        assert!(lines("/test.cfg", ": range = 6", ':', "name: ...").is_err());
+    }
+
+    #[test]
+    fn errors_name_the_file_and_line() {
+        let error = match lines("/test.cfg", "ok: 1\n\nbroken", ':', "name: ...") {
+            Err(GameError::ConfigError(message)) => message,
+            _ => panic!("Expected a config error"),
+        };
+
+        assert!(error.starts_with("/test.cfg:3: "));
+    }
+
+    #[test]
+    fn lists_skip_empty_items() {
+        let items: Vec<&str> = list(" Left, ,Numpad4 ,, H ").collect();
+
+        assert_eq!(items, vec!["Left", "Numpad4", "H"]);
+    }
+
+    #[test]
+    fn splits_key_values() {
+        assert_eq!(key_value(" range = 6 "), ("range", "6"));
+        assert_eq!(key_value("verb = a = b"), ("verb", "a = b"));
+        assert_eq!(key_value("range"), ("range", ""));
+    }
+}
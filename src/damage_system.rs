@@ -1,17 +1,99 @@
 extern crate specs;
-use super::{CombatStats, SufferDamage, Player};
+use super::{
+    gamelog::GameLog, gamestats::GameStats, spawner, Asleep, Attributes, CombatStats, Experience,
+    GrantsXp, GridPosition, ItemSpawner, KilledBy, LootTable, Name, Player, Renderable, Resistant,
+    RunState, SufferDamage, Vulnerable,
+};
+use ggez::graphics as gfx;
+use rltk::RandomNumberGenerator;
 use specs::prelude::*;
 
+/// Everything needed to leave remains behind once the entity itself is gone
+struct Remains {
+    x: i32,
+    y: i32,
+    name: String,
+    color: gfx::Color,
+    loot: Option<ItemSpawner>,
+}
+
 pub struct DamageSystem {}
 
 impl<'a> System<'a> for DamageSystem {
-    type SystemData = (WriteStorage<'a, CombatStats>, WriteStorage<'a, SufferDamage>);
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        WriteExpect<'a, GameLog>,
+        WriteStorage<'a, CombatStats>,
+        WriteStorage<'a, SufferDamage>,
+        WriteStorage<'a, KilledBy>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Resistant>,
+        ReadStorage<'a, Vulnerable>,
+        WriteStorage<'a, Asleep>,
+    );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut stats, mut damage) = data;
+        let (
+            entities,
+            mut log,
+            mut stats,
+            mut damage,
+            mut killed_by,
+            names,
+            resistances,
+            vulnerabilities,
+            mut asleep,
+        ) = data;
+
+        for (entity, stats, damage) in (&entities, &mut stats, &damage).join() {
+            // Nobody sleeps through getting hurt
+            asleep.remove(entity);
+
+            for instance in damage.instances.iter() {
+                let resists = resistances
+                    .get(entity)
+                    .is_some_and(|r| r.kinds.contains(&instance.kind));
+                let vulnerable = vulnerabilities
+                    .get(entity)
+                    .is_some_and(|v| v.kinds.contains(&instance.kind));
+
+                let mut amount = instance.amount;
+                if resists {
+                    amount /= 2;
+                }
+                if vulnerable {
+                    amount *= 2;
+                }
+
+                // Logged here, so the amount is what was actually lost
+                if let Some(name) = names.get(entity) {
+                    let kind = instance.kind.name();
+                    let entry = if resists && !vulnerable {
+                        format!("{} resists, taking only {} {} damage.\n", name.name, amount, kind)
+                    } else if vulnerable && !resists {
+                        format!("{} is badly hurt, taking {} {} damage!\n", name.name, amount, kind)
+                    } else {
+                        format!("{} takes {} {} damage.\n", name.name, amount, kind)
+                    };
+                    log.entries.push(entry);
+                }
+
+                let was_alive = stats.hp > 0;
+                stats.hp -= amount;
 
-        for (mut stats, damage) in (&mut stats, &damage).join() {
-            stats.hp -= damage.amount;
+                // Remember who dealt the killing blow
+                if was_alive && stats.hp < 1 {
+                    killed_by
+                        .insert(
+                            entity,
+                            KilledBy {
+                                killer: instance.source,
+                            },
+                        )
+                        .expect("Unable to insert KilledBy");
+                }
+            }
         }
         // We've processed all SufferDamage comps, so clear them
         damage.clear();
@@ -20,26 +102,124 @@ impl<'a> System<'a> for DamageSystem {
 
 pub fn delete_the_dead(ecs: &mut World) {
     let mut dead: Vec<Entity> = Vec::new();
+    let mut xp_awards: Vec<(Entity, i32)> = Vec::new();
+    let mut remains: Vec<Remains> = Vec::new();
     // Using a scope to make the borrow checker happy (at ecs.delete_entity)
     {
 
       let combat_stats = ecs.read_storage::<CombatStats>();
       let players = ecs.read_storage::<Player>();
+      let names = ecs.read_storage::<Name>();
+      let killed_by = ecs.read_storage::<KilledBy>();
+      let grants_xp = ecs.read_storage::<GrantsXp>();
+      let positions = ecs.read_storage::<GridPosition>();
+      let renderables = ecs.read_storage::<Renderable>();
+      let loot_tables = ecs.read_storage::<LootTable>();
+      let mut rng = ecs.write_resource::<RandomNumberGenerator>();
       let entities = ecs.entities();
-      
-      for (entity, stats) in (&entities, &combat_stats).join() {
-        if stats.hp < 1 {
+      let mut log = ecs.write_resource::<GameLog>();
+      let mut stats = ecs.write_resource::<GameStats>();
+      let mut runstate = ecs.write_resource::<RunState>();
+
+      for (entity, combat_stats) in (&entities, &combat_stats).join() {
+        if combat_stats.hp < 1 {
           let player = players.get(entity);
           match player {
-            None => dead.push(entity),  // Not the player, so delete it
-            Some(_) => println!("You are dead!!!!"),
+            None => {
+              // Not the player, so delete it and reward whoever killed it
+              dead.push(entity);
+              let killer = killed_by.get(entity).and_then(|k| k.killer);
+              if let (Some(killer), Some(grants)) = (killer, grants_xp.get(entity)) {
+                xp_awards.push((killer, grants.xp));
+              }
+
+              // Leave a corpse, and maybe some loot, where it fell
+              if let (Some(pos), Some(name)) = (positions.get(entity), names.get(entity)) {
+                remains.push(Remains {
+                  x: pos.x,
+                  y: pos.y,
+                  name: name.name.clone(),
+                  color: renderables.get(entity).map_or(gfx::WHITE, |r| r.color),
+                  loot: loot_tables.get(entity).and_then(|t| t.roll(&mut rng)),
+                });
+              }
+            }
+            Some(_) => {
+              if *runstate != RunState::GameOver {
+                let killer_name = killed_by
+                  .get(entity)
+                  .and_then(|k| k.killer)
+                  .and_then(|k| names.get(k));
+
+                let cause = match killer_name {
+                  Some(name) => format!("Killed by {}", name.name),
+                  None => "Succumbed to their wounds".to_string(),
+                };
+
+                log.entries.push("You are dead!!!!".to_string());
+                stats.cause_of_death = Some(cause);
+                *runstate = RunState::GameOver;
+              }
+            }
           }
         }
       }
     }
 
+    award_xp(ecs, &xp_awards);
+
     for victim in dead {
         ecs.delete_entity(victim)
             .expect("Unable to delete dead entity");
     }
+
+    for remain in remains {
+        spawner::corpse(ecs, remain.x, remain.y, &remain.name, remain.color);
+        if let Some(spawn) = remain.loot {
+            spawn(ecs, remain.x, remain.y);
+        }
+    }
+}
+
+/// Hands out XP for kills. Crossing the threshold levels the killer up,
+/// making them tougher and fully healing them.
+fn award_xp(ecs: &World, awards: &[(Entity, i32)]) {
+    let mut experience = ecs.write_storage::<Experience>();
+    let mut combat_stats = ecs.write_storage::<CombatStats>();
+    let players = ecs.read_storage::<Player>();
+    let attributes = ecs.read_storage::<Attributes>();
+    let mut log = ecs.write_resource::<GameLog>();
+
+    for (killer, xp) in awards.iter() {
+        let exp = match experience.get_mut(*killer) {
+            Some(exp) => exp,
+            None => continue,
+        };
+        let is_player = players.get(*killer).is_some();
+
+        exp.xp += xp;
+        if is_player {
+            log.entries.push(format!("You gain {} xp.", xp));
+        }
+
+        while exp.xp >= exp.xp_to_next_level() {
+            exp.xp -= exp.xp_to_next_level();
+            exp.level += 1;
+
+            if let Some(stats) = combat_stats.get_mut(*killer) {
+                let fitness_bonus = attributes.get(*killer).map_or(0, |a| a.fitness_bonus());
+                stats.max_hp += i32::max(1, 10 + fitness_bonus);
+                stats.power += 1;
+                if exp.level % 2 == 0 {
+                    stats.defense += 1;
+                }
+                stats.hp = stats.max_hp;
+            }
+
+            if is_player {
+                log.entries
+                    .push(format!("Welcome to level {}! You feel stronger.", exp.level));
+            }
+        }
+    }
 }
@@ -0,0 +1,117 @@
+use ggez::{Context, GameResult};
+
+use std::collections::HashMap;
+
+use super::config_file;
+
+/// Path (inside ggez's resource dirs) of the faction reactions file
+pub const FACTIONS_FILE: &str = "/factions.cfg";
+
+/// How members of one faction treat members of another
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Reaction {
+    Attack,
+    Ignore,
+    Flee,
+}
+
+impl Reaction {
+    fn from_name(name: &str) -> Option<Reaction> {
+        match name {
+            "attack" => Some(Reaction::Attack),
+            "ignore" => Some(Reaction::Ignore),
+            "flee" => Some(Reaction::Flee),
+            _ => None,
+        }
+    }
+}
+
+/// Who reacts how to whom. Anything not listed is ignored.
+#[derive(Clone)]
+pub struct FactionTable {
+    reactions: HashMap<(String, String), Reaction>,
+}
+
+impl FactionTable {
+    /// Loads the factions file from the ggez filesystem
+    pub fn load(ctx: &mut Context) -> GameResult<FactionTable> {
+        let contents = config_file::read(ctx, FACTIONS_FILE)?;
+        FactionTable::parse(&contents)
+    }
+
+    /// Parses lines of the form `faction: other = reaction, other = reaction, ...`
+    pub fn parse(contents: &str) -> GameResult<FactionTable> {
+        let mut reactions: HashMap<(String, String), Reaction> = HashMap::new();
+
+        let expected = "faction: other = reaction, ...";
+        for line in config_file::lines(FACTIONS_FILE, contents, ':', expected)? {
+            for entry in config_file::list(line.rest) {
+                let (other, reaction_name) = config_file::key_value(entry);
+                let reaction = Reaction::from_name(reaction_name).ok_or_else(|| {
+                    line.error(format!(
+                        "unknown reaction `{}` towards `{}`",
+                        reaction_name, other
+                    ))
+                })?;
+
+                reactions.insert((line.head.to_string(), other.to_string()), reaction);
+            }
+        }
+
+        Ok(FactionTable { reactions })
+    }
+
+    /// How `mine` reacts to `theirs`. Members of the same faction leave each other alone.
+    pub fn reaction(&self, mine: &str, theirs: &str) -> Reaction {
+        if mine == theirs {
+            return Reaction::Ignore;
+        }
+
+        self.reactions
+            .get(&(mine.to_string(), theirs.to_string()))
+            .copied()
+            .unwrap_or(Reaction::Ignore)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_reactions_towards_other_factions() {
+        let table = FactionTable::parse("goblins: player = attack, orcs = flee").unwrap();
+
+        assert_eq!(table.reaction("goblins", "player"), Reaction::Attack);
+        assert_eq!(table.reaction("goblins", "orcs"), Reaction::Flee);
+    }
+
+    #[test]
+    fn reactions_only_go_one_way() {
+        let table = FactionTable::parse("goblins: orcs = flee").unwrap();
+
+        assert_eq!(table.reaction("orcs", "goblins"), Reaction::Ignore);
+    }
+
+    #[test]
+    fn ignores_unlisted_and_own_faction() {
+        let table = FactionTable::parse("orcs: orcs = attack").unwrap();
+
+        assert_eq!(table.reaction("orcs", "orcs"), Reaction::Ignore);
+        assert_eq!(table.reaction("orcs", "kobolds"), Reaction::Ignore);
+    }
+
+    #[test]
+    fn rejects_unknown_reactions() {
+        assert!(FactionTable::parse("orcs: player = hug").is_err());
+        assert!(FactionTable::parse("orcs: player").is_err());
+    }
+
+    #[test]
+    fn default_factions_file_parses() {
+        let table = FactionTable::parse(include_str!("../resources/factions.cfg")).unwrap();
+
+        assert_eq!(table.reaction("player", "goblins"), Reaction::Attack);
+        assert_eq!(table.reaction("goblins", "orcs"), Reaction::Flee);
+    }
+}
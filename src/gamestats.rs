@@ -0,0 +1,4 @@
+pub struct GameStats {
+  pub turns: i32,
+  pub cause_of_death: Option<String>,
+}
@@ -6,9 +6,16 @@ use ggez::graphics as gfx;
 use ggez::{Context, GameResult};
 
 use super::na;
-use super::{CombatStats, Player};
+use super::{
+    AiState, Asleep, Brain, CombatStats, Experience, GridPosition, HungerClock, HungerState, Map,
+    Player,
+};
 
 use super::gamelog::GameLog;
+use super::gamestats::GameStats;
+use super::keybindings::KeyBindings;
+use super::menu::{self, MainMenuSelection};
+use super::targeting;
 
 use crate::map::GRID_TILE_SIZE;
 
@@ -58,6 +65,33 @@ pub fn draw_ui(ecs: &World, ctx: &mut Context) -> GameResult {
         )?;
     }
 
+    // Draw the hunger state beside the health bar
+    let hunger_clocks = ecs.read_storage::<HungerClock>();
+    let x = GRID_TILE_SIZE * 37; // @TODO @HARDCODED
+    let y = GRID_TILE_SIZE * 43; // @TODO @HARDCODED
+    for (_player, clock) in (&players, &hunger_clocks).join() {
+        draw_hunger_status(ctx, clock.state, x as f32, y as f32)?;
+    }
+
+    // Draw the level and xp to the left of the health bar
+    let experience = ecs.read_storage::<Experience>();
+    let x = GRID_TILE_SIZE * 16; // @TODO @HARDCODED
+    let y = GRID_TILE_SIZE * 43; // @TODO @HARDCODED
+    for (_player, exp) in (&players, &experience).join() {
+        let level_text = gfx::Text::new(gfx::TextFragment {
+            text: format!(
+                "Level {}  XP {}/{}",
+                exp.level,
+                exp.xp,
+                exp.xp_to_next_level()
+            ),
+            color: Some(gfx::Color::new(1.0, 1.0, 0.0, 1.0)),
+            scale: Some(gfx::Scale::uniform(10.0)),
+            ..Default::default()
+        });
+        gfx::draw(ctx, &level_text, (na::Point2::new(x as f32, y as f32),))?;
+    }
+
     let x: i32 = GRID_TILE_SIZE * 2; // @TODO @HARDCODED
     let y: i32 = GRID_TILE_SIZE * 44; // @TODO @HARDCODED
     let max_y: i32 = GRID_TILE_SIZE * 49; // @TODO @HARDCODED
@@ -121,6 +155,25 @@ fn draw_health_bar(
     Ok(())
 }
 
+fn draw_hunger_status(ctx: &mut Context, state: HungerState, x: f32, y: f32) -> GameResult {
+    let (label, color) = match state {
+        HungerState::WellFed => ("Well Fed", gfx::Color::new(0.0, 1.0, 0.0, 1.0)),
+        HungerState::Normal => ("Normal", gfx::WHITE),
+        HungerState::Hungry => ("Hungry", gfx::Color::new(1.0, 0.65, 0.0, 1.0)),
+        HungerState::Starving => ("Starving", gfx::Color::new(1.0, 0.0, 0.0, 1.0)),
+    };
+
+    let hunger_text = gfx::Text::new(gfx::TextFragment {
+        text: label.to_string(),
+        color: Some(color),
+        scale: Some(gfx::Scale::uniform(10.0)),
+        ..Default::default()
+    });
+    gfx::draw(ctx, &hunger_text, (na::Point2::new(x, y),))?;
+
+    Ok(())
+}
+
 // @TODO: Pull text settings to a struct var?
 
 fn draw_log(ctx: &mut Context, log: &GameLog, x: f32, y: f32, max_y: f32) -> GameResult {
@@ -154,3 +207,419 @@ fn draw_log(ctx: &mut Context, log: &GameLog, x: f32, y: f32, max_y: f32) -> Gam
 
     Ok(())
 }
+
+/// Death screen: cause of death, turn count and how to start over
+pub fn draw_game_over(ecs: &World, ctx: &mut Context) -> GameResult {
+    let stats = ecs.fetch::<GameStats>();
+
+    // Darken the game behind the text
+    let screen_w = GRID_TILE_SIZE * 80; // @TODO @HARDCODED
+    let screen_h = GRID_TILE_SIZE * 50; // @TODO @HARDCODED
+    let backdrop = gfx::Mesh::new_rectangle(
+        ctx,
+        gfx::DrawMode::fill(),
+        gfx::Rect::new_i32(0, 0, screen_w, screen_h),
+        gfx::Color::new(0.0, 0.0, 0.0, 0.8),
+    )?;
+    gfx::draw(ctx, &backdrop, gfx::DrawParam::default())?;
+
+    let cause = match &stats.cause_of_death {
+        Some(cause) => cause.clone(),
+        None => "Died of unknown causes".to_string(),
+    };
+
+    let lines = [
+        ("You are dead!".to_string(), gfx::Color::new(1.0, 0.1, 0.1, 1.0)),
+        (cause, gfx::WHITE),
+        (
+            format!("You survived for {} turns.", stats.turns),
+            gfx::WHITE,
+        ),
+        (
+            "Press any key to start a new game.".to_string(),
+            gfx::Color::new(1.0, 1.0, 0.0, 1.0),
+        ),
+    ];
+
+    let mut y = (GRID_TILE_SIZE * 15) as f32; // @TODO @HARDCODED
+    for (line, color) in lines.iter() {
+        draw_centered_text(ctx, line, y, *color, screen_w as f32)?;
+        y += (GRID_TILE_SIZE * 3) as f32;
+    }
+
+    Ok(())
+}
+
+fn draw_centered_text(
+    ctx: &mut Context,
+    text: &str,
+    y: f32,
+    color: gfx::Color,
+    screen_w: f32,
+) -> GameResult {
+    let text = gfx::Text::new(gfx::TextFragment {
+        text: text.to_string(),
+        color: Some(color),
+        scale: Some(gfx::Scale::uniform(16.0)),
+        ..Default::default()
+    });
+
+    let x = (screen_w - text.width(ctx) as f32) / 2.0;
+    gfx::draw(ctx, &text, (na::Point2::new(x, y),))?;
+
+    Ok(())
+}
+
+/// Title graphic plus the main menu entries, highlighting the selected one.
+/// `in_game` adds a reminder of how to get back to the game.
+pub fn draw_main_menu(
+    ctx: &mut Context,
+    title: &rltk::rex::XpFile,
+    selection: MainMenuSelection,
+    in_game: bool,
+) -> GameResult {
+    let screen_w = (GRID_TILE_SIZE * 80) as f32; // @TODO @HARDCODED
+
+    // Title graphic, centered at the top
+    draw_xp_image(ctx, title, GRID_TILE_SIZE * 2)?;
+
+    draw_centered_text(
+        ctx,
+        "THAT GAME",
+        (GRID_TILE_SIZE * 25) as f32,
+        gfx::Color::new(1.0, 1.0, 0.0, 1.0),
+        screen_w,
+    )?;
+
+    // Menu entries
+    let mut entries_mb = gfx::MeshBuilder::new();
+    for (i, entry) in menu::ENTRIES.iter().enumerate() {
+        let rect = menu::entry_rect(i);
+        let (bg_color, fg_color) = if *entry == selection {
+            (gfx::Color::new(1.0, 1.0, 1.0, 1.0), gfx::BLACK)
+        } else {
+            (gfx::Color::new(0.2, 0.2, 0.2, 1.0), gfx::WHITE)
+        };
+        entries_mb.rectangle(gfx::DrawMode::fill(), rect, bg_color);
+
+        let text = gfx::Text::new(gfx::TextFragment {
+            text: entry.label().to_string(),
+            color: Some(fg_color),
+            scale: Some(gfx::Scale::uniform(14.0)),
+            ..Default::default()
+        });
+        let x = rect.x + (rect.w - text.width(ctx) as f32) / 2.0;
+        let y = rect.y + (rect.h - text.height(ctx) as f32) / 2.0;
+        gfx::queue_text(ctx, &text, na::Point2::new(x, y), None);
+    }
+
+    if in_game {
+        let back_y = menu::entry_rect(menu::ENTRIES.len()).y;
+        draw_centered_text(
+            ctx,
+            "Escape to go back to the game",
+            back_y,
+            gfx::Color::new(0.6, 0.6, 0.6, 1.0),
+            screen_w,
+        )?;
+    }
+
+    let entries_mesh = entries_mb.build(ctx)?;
+    gfx::draw(ctx, &entries_mesh, gfx::DrawParam::default())?;
+    gfx::draw_queued_text(
+        ctx,
+        gfx::DrawParam::default(),
+        None,
+        gfx::FilterMode::Linear,
+    )?;
+
+    Ok(())
+}
+
+/// Options screen. For now it just lists the current key bindings.
+pub fn draw_options(ctx: &mut Context, keybindings: &KeyBindings) -> GameResult {
+    let screen_w = (GRID_TILE_SIZE * 80) as f32; // @TODO @HARDCODED
+
+    draw_centered_text(
+        ctx,
+        "Key bindings",
+        (GRID_TILE_SIZE * 3) as f32,
+        gfx::Color::new(1.0, 1.0, 0.0, 1.0),
+        screen_w,
+    )?;
+
+    let mut y = GRID_TILE_SIZE * 7;
+    for line in keybindings.describe() {
+        let text = gfx::Text::new(gfx::TextFragment {
+            text: line,
+            scale: Some(gfx::Scale::uniform(12.0)),
+            ..Default::default()
+        });
+        gfx::queue_text(
+            ctx,
+            &text,
+            na::Point2::new((GRID_TILE_SIZE * 10) as f32, y as f32),
+            None,
+        );
+        y += GRID_TILE_SIZE * 2;
+    }
+    gfx::draw_queued_text(
+        ctx,
+        gfx::DrawParam::default(),
+        None,
+        gfx::FilterMode::Linear,
+    )?;
+
+    draw_centered_text(
+        ctx,
+        "Edit resources/keybindings.cfg to change them. Press any key to go back.",
+        (GRID_TILE_SIZE * 46) as f32,
+        gfx::WHITE,
+        screen_w,
+    )?;
+
+    Ok(())
+}
+
+/// Draws a REX Paint image one grid tile per cell, horizontally centered at `y`.
+/// Only cell backgrounds are drawn (skipping REX Paint's transparent magenta),
+/// plus any plain ASCII glyphs on top.
+fn draw_xp_image(ctx: &mut Context, image: &rltk::rex::XpFile, y: i32) -> GameResult {
+    let mut image_mb = gfx::MeshBuilder::new();
+    let mut has_cells = false;
+
+    for layer in image.layers.iter() {
+        let x = (GRID_TILE_SIZE * 80 - layer.width as i32 * GRID_TILE_SIZE) / 2; // @TODO @HARDCODED
+
+        for cell_y in 0..layer.height {
+            for cell_x in 0..layer.width {
+                let cell = match layer.get(cell_x, cell_y) {
+                    Some(cell) => cell,
+                    None => continue,
+                };
+
+                let is_transparent = cell.bg.r == 255 && cell.bg.g == 0 && cell.bg.b == 255;
+                if is_transparent {
+                    continue;
+                }
+
+                let cell_px = x + cell_x as i32 * GRID_TILE_SIZE;
+                let cell_py = y + cell_y as i32 * GRID_TILE_SIZE;
+
+                let rect = gfx::Rect::new_i32(cell_px, cell_py, GRID_TILE_SIZE, GRID_TILE_SIZE);
+                image_mb.rectangle(
+                    gfx::DrawMode::fill(),
+                    rect,
+                    gfx::Color::from_rgb(cell.bg.r, cell.bg.g, cell.bg.b),
+                );
+                has_cells = true;
+
+                if cell.ch > 32 && cell.ch < 127 {
+                    let glyph = gfx::Text::new(gfx::TextFragment {
+                        text: (cell.ch as u8 as char).to_string(),
+                        color: Some(gfx::Color::from_rgb(cell.fg.r, cell.fg.g, cell.fg.b)),
+                        scale: Some(gfx::Scale::uniform(GRID_TILE_SIZE as f32)),
+                        ..Default::default()
+                    });
+                    gfx::queue_text(
+                        ctx,
+                        &glyph,
+                        na::Point2::new(cell_px as f32, cell_py as f32),
+                        None,
+                    );
+                }
+            }
+        }
+    }
+
+    // Building an empty mesh is an error, so only draw if we actually have something
+    if has_cells {
+        let image_mesh = image_mb.build(ctx)?;
+        gfx::draw(ctx, &image_mesh, gfx::DrawParam::default())?;
+    }
+    gfx::draw_queued_text(
+        ctx,
+        gfx::DrawParam::default(),
+        None,
+        gfx::FilterMode::Linear,
+    )?;
+
+    Ok(())
+}
+
+/// Lettered list of items, used by every item picking menu
+pub fn draw_item_menu(ctx: &mut Context, title: &str, items: &[(Entity, String)]) -> GameResult {
+    let x = GRID_TILE_SIZE * 15; // @TODO @HARDCODED
+    let w = GRID_TILE_SIZE * 31;
+    let y = GRID_TILE_SIZE * 25 - (items.len() as i32 + 4) * GRID_TILE_SIZE;
+    let h = (items.len() as i32 + 5) * GRID_TILE_SIZE * 2;
+
+    let box_mesh = gfx::MeshBuilder::new()
+        .rectangle(
+            gfx::DrawMode::fill(),
+            gfx::Rect::new_i32(x, y, w, h),
+            gfx::BLACK,
+        )
+        .rectangle(
+            gfx::DrawMode::stroke(1.0),
+            gfx::Rect::new_i32(x, y, w, h),
+            gfx::WHITE,
+        )
+        .build(ctx)?;
+    gfx::draw(ctx, &box_mesh, gfx::DrawParam::default())?;
+
+    let title_text = gfx::Text::new(gfx::TextFragment {
+        text: title.to_string(),
+        color: Some(gfx::Color::new(1.0, 1.0, 0.0, 1.0)),
+        scale: Some(gfx::Scale::uniform(12.0)),
+        ..Default::default()
+    });
+    gfx::queue_text(
+        ctx,
+        &title_text,
+        na::Point2::new((x + GRID_TILE_SIZE) as f32, (y + GRID_TILE_SIZE) as f32),
+        None,
+    );
+
+    let mut line_y = y + GRID_TILE_SIZE * 4;
+    for (i, (_item, name)) in items.iter().enumerate() {
+        let line = gfx::Text::new(gfx::TextFragment {
+            text: format!("({}) {}", menu::option_letter(i), name),
+            scale: Some(gfx::Scale::uniform(12.0)),
+            ..Default::default()
+        });
+        gfx::queue_text(
+            ctx,
+            &line,
+            na::Point2::new((x + GRID_TILE_SIZE * 2) as f32, line_y as f32),
+            None,
+        );
+        line_y += GRID_TILE_SIZE * 2;
+    }
+
+    let footer = gfx::Text::new(gfx::TextFragment {
+        text: "ESCAPE to cancel".to_string(),
+        color: Some(gfx::Color::new(1.0, 1.0, 0.0, 1.0)),
+        scale: Some(gfx::Scale::uniform(12.0)),
+        ..Default::default()
+    });
+    gfx::queue_text(
+        ctx,
+        &footer,
+        na::Point2::new((x + GRID_TILE_SIZE) as f32, (line_y + GRID_TILE_SIZE) as f32),
+        None,
+    );
+
+    gfx::draw_queued_text(
+        ctx,
+        gfx::DrawParam::default(),
+        None,
+        gfx::FilterMode::Linear,
+    )?;
+
+    Ok(())
+}
+
+/// Targeting overlay: valid tiles in range, the line of fire and the cursor
+pub fn draw_targeting(
+    ecs: &World,
+    ctx: &mut Context,
+    range: i32,
+    cursor: rltk::Point,
+) -> GameResult {
+    let player_pos = ecs.fetch::<rltk::Point>();
+    let targets = targeting::valid_targets(ecs, range);
+    let cursor_valid = targets.contains(&cursor);
+
+    let tile_rect = |p: &rltk::Point| {
+        gfx::Rect::new_i32(
+            p.x * GRID_TILE_SIZE,
+            p.y * GRID_TILE_SIZE,
+            GRID_TILE_SIZE,
+            GRID_TILE_SIZE,
+        )
+    };
+
+    let mut targeting_mb = gfx::MeshBuilder::new();
+
+    // Highlight every tile we could target
+    for tile in targets.iter() {
+        targeting_mb.rectangle(
+            gfx::DrawMode::fill(),
+            tile_rect(tile),
+            gfx::Color::new(0.0, 0.5, 1.0, 0.4),
+        );
+    }
+
+    // Line of fire from the player to the cursor
+    if cursor != *player_pos {
+        let line = rltk::line2d(rltk::LineAlg::Bresenham, *player_pos, cursor);
+        for step in line.iter().filter(|p| **p != *player_pos && **p != cursor) {
+            targeting_mb.rectangle(
+                gfx::DrawMode::fill(),
+                tile_rect(step),
+                gfx::Color::new(1.0, 1.0, 0.0, 0.5),
+            );
+        }
+    }
+
+    // The cursor itself
+    let cursor_color = if cursor_valid {
+        gfx::Color::new(0.0, 1.0, 1.0, 1.0)
+    } else {
+        gfx::Color::new(1.0, 0.0, 0.0, 1.0)
+    };
+    targeting_mb.rectangle(gfx::DrawMode::stroke(1.0), tile_rect(&cursor), cursor_color);
+
+    let targeting_mesh = targeting_mb.build(ctx)?;
+    gfx::draw(ctx, &targeting_mesh, gfx::DrawParam::default())?;
+
+    let help = gfx::Text::new(gfx::TextFragment {
+        text: "Select target: move the cursor, ENTER or click to confirm, ESCAPE to cancel"
+            .to_string(),
+        color: Some(gfx::Color::new(1.0, 1.0, 0.0, 1.0)),
+        scale: Some(gfx::Scale::uniform(10.0)),
+        ..Default::default()
+    });
+    gfx::draw(
+        ctx,
+        &help,
+        (na::Point2::new(GRID_TILE_SIZE as f32, GRID_TILE_SIZE as f32),),
+    )?;
+
+    Ok(())
+}
+
+/// Labels every visible monster with its AI state
+pub fn draw_ai_debug(ecs: &World, ctx: &mut Context) -> GameResult {
+    let map = ecs.fetch::<Map>();
+    let positions = ecs.read_storage::<GridPosition>();
+    let brains = ecs.read_storage::<Brain>();
+    let asleep = ecs.read_storage::<Asleep>();
+    let entities = ecs.entities();
+
+    for (entity, pos, brain) in (&entities, &positions, &brains).join() {
+        let idx = map.xy_idx(pos.x, pos.y);
+        if !map.visible_tiles[idx] {
+            continue;
+        }
+
+        let label = match brain.state {
+            _ if asleep.get(entity).is_some() => "asleep".to_string(),
+            AiState::Search { target } => format!("search {},{}", target.x, target.y),
+            state => state.label().to_string(),
+        };
+        let debug_text = gfx::Text::new(gfx::TextFragment {
+            text: label,
+            color: Some(gfx::Color::new(1.0, 1.0, 0.0, 1.0)),
+            scale: Some(gfx::Scale::uniform(8.0)),
+            ..Default::default()
+        });
+
+        let x = ((pos.x + 1) * GRID_TILE_SIZE) as f32;
+        let y = ((pos.y - 1) * GRID_TILE_SIZE) as f32;
+        gfx::draw(ctx, &debug_text, (na::Point2::new(x, y),))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+extern crate specs;
+use super::{AiState, Asleep, Brain, Follower, GridPosition, Map, Noise};
+use specs::prelude::*;
+
+extern crate rltk;
+use rltk::{Point, RandomNumberGenerator};
+
+/// Noise spreads out from where it was made, going around walls the way pathfinding does.
+/// It gets harder to hear the further it has travelled, and is gone past its volume.
+/// Monsters that hear it come over to take a look.
+pub struct HearingSystem {}
+
+impl<'a> System<'a> for HearingSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        ReadExpect<'a, Map>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        Entities<'a>,
+        WriteStorage<'a, Noise>,
+        ReadStorage<'a, GridPosition>,
+        WriteStorage<'a, Brain>,
+        ReadStorage<'a, Follower>,
+        WriteStorage<'a, Asleep>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (map, mut rng, entities, mut noises, positions, mut brains, followers, mut asleep) =
+            data;
+
+        for (noisy, noise, source) in (&entities, &noises, &positions).join() {
+            let source_idx = map.xy_idx(source.x, source.y);
+            let distances = map.dijkstra_map(&[source_idx], noise.volume as f32);
+
+            for (entity, brain, pos) in (&entities, &mut brains, &positions).join() {
+                // Nobody investigates their own noise, and allies stick with the player
+                if entity == noisy || followers.get(entity).is_some() {
+                    continue;
+                }
+
+                let distance = distances[map.xy_idx(pos.x, pos.y)];
+                if !hears(&mut rng, distance, noise.volume) {
+                    continue;
+                }
+
+                // Loud enough to wake anyone up
+                asleep.remove(entity);
+
+                // Only monsters with nothing better to do go and have a look
+                let idle = matches!(brain.state, AiState::Wander | AiState::Search { .. });
+                if idle {
+                    brain.state = AiState::Search {
+                        target: Point::new(source.x, source.y),
+                    };
+                }
+            }
+        }
+
+        // Every noise is only heard the turn it was made
+        noises.clear();
+    }
+}
+
+/// Chance of hearing a noise drops off evenly with distance, down to nothing past its volume
+fn hears(rng: &mut RandomNumberGenerator, distance: f32, volume: i32) -> bool {
+    if distance > volume as f32 {
+        return false;
+    }
+    let chance = 100 - (distance * 100.0 / volume as f32) as i32;
+    rng.roll_dice(1, 100) <= chance.max(1)
+}
@@ -0,0 +1,66 @@
+extern crate specs;
+use super::{gamelog::GameLog, DamageType, HungerClock, HungerState, Player, SufferDamage};
+use specs::prelude::*;
+
+/// Turns spent in each hunger state before getting hungrier
+pub const HUNGER_STATE_DURATION: i32 = 200;
+/// How long a meal keeps you well fed
+pub const WELL_FED_DURATION: i32 = 20;
+
+pub struct HungerSystem {}
+
+impl<'a> System<'a> for HungerSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        WriteExpect<'a, GameLog>,
+        Entities<'a>,
+        WriteStorage<'a, HungerClock>,
+        ReadStorage<'a, Player>,
+        WriteStorage<'a, SufferDamage>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (mut gamelog, entities, mut hunger_clocks, players, mut suffer_damage) = data;
+
+        for (entity, clock, _player) in (&entities, &mut hunger_clocks, &players).join() {
+            clock.duration -= 1;
+            if clock.duration > 0 {
+                continue;
+            }
+
+            match clock.state {
+                HungerState::WellFed => {
+                    clock.state = HungerState::Normal;
+                    clock.duration = HUNGER_STATE_DURATION;
+                    gamelog
+                        .entries
+                        .push("You are no longer well fed.".to_string());
+                }
+                HungerState::Normal => {
+                    clock.state = HungerState::Hungry;
+                    clock.duration = HUNGER_STATE_DURATION;
+                    gamelog.entries.push("You are hungry.".to_string());
+                }
+                HungerState::Hungry => {
+                    clock.state = HungerState::Starving;
+                    clock.duration = HUNGER_STATE_DURATION;
+                    gamelog.entries.push("You are starving!".to_string());
+                }
+                HungerState::Starving => {
+                    // Starving hurts, every turn
+                    clock.duration = 0;
+                    SufferDamage::new_damage(
+                        &mut suffer_damage,
+                        entity,
+                        1,
+                        DamageType::Starvation,
+                        None,
+                    );
+                    gamelog
+                        .entries
+                        .push("Your hunger pangs are getting painful!".to_string());
+                }
+            }
+        }
+    }
+}
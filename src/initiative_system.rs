@@ -0,0 +1,62 @@
+extern crate specs;
+use super::{Energy, MyTurn, StatusEffects};
+use specs::prelude::*;
+
+/// Energy it takes to act once
+pub const ACTION_COST: i32 = 100;
+/// Speed of an average creature, so they act once every 10 ticks
+pub const NORMAL_SPEED: i32 = 10;
+/// Ticks in one turn of game time, the time an average creature takes to act
+pub const TICKS_PER_TURN: i32 = ACTION_COST / NORMAL_SPEED;
+
+/// Counts the ticks handed out since the game started
+#[derive(Default)]
+pub struct GameClock {
+    pub ticks: i32,
+}
+
+impl GameClock {
+    /// True when the last tick completed a turn of game time
+    pub fn turn_passed(&self) -> bool {
+        self.ticks % TICKS_PER_TURN == 0
+    }
+}
+
+/// One tick of the clock. Everyone builds up energy by their speed, and whoever
+/// has enough spends it and gets a `MyTurn` until the next tick.
+pub struct InitiativeSystem {}
+
+impl<'a> System<'a> for InitiativeSystem {
+    type SystemData = (
+        WriteExpect<'a, GameClock>,
+        Entities<'a>,
+        WriteStorage<'a, Energy>,
+        WriteStorage<'a, MyTurn>,
+        ReadStorage<'a, StatusEffects>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (mut clock, entities, mut energies, mut turns, status_effects) = data;
+
+        clock.ticks += 1;
+
+        // Whoever had a turn has taken it by now
+        turns.clear();
+
+        for (entity, energy) in (&entities, &mut energies).join() {
+            let hasted = status_effects.get(entity).is_some_and(|s| s.is_hasted());
+            energy.current += if hasted {
+                energy.speed * 2
+            } else {
+                energy.speed
+            };
+
+            if energy.current >= ACTION_COST {
+                energy.current -= ACTION_COST;
+                turns
+                    .insert(entity, MyTurn {})
+                    .expect("Unable to insert MyTurn");
+            }
+        }
+    }
+}
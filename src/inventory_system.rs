@@ -0,0 +1,377 @@
+extern crate specs;
+use super::hunger_system::WELL_FED_DURATION;
+use super::initiative_system::ACTION_COST;
+use super::melee_combat_system::practice_skill;
+use super::{
+    gamelog::GameLog, AreaOfEffect, Attributes, CombatStats, Consumable, Energy, EquipmentSlot,
+    Equippable, Equipped, GridPosition, HungerClock, HungerState, InBackpack, InflictsDamage,
+    InflictsStatus, Map, Name, ProvidesFood, ProvidesHealing, Skills, StatusEffects, SufferDamage,
+    WantsToDropItem, WantsToPickupItem, WantsToRemoveItem, WantsToUseItem,
+};
+use specs::prelude::*;
+
+pub struct ItemCollectionSystem {}
+
+impl<'a> System<'a> for ItemCollectionSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        WriteStorage<'a, WantsToPickupItem>,
+        WriteStorage<'a, GridPosition>,
+        ReadStorage<'a, Name>,
+        WriteStorage<'a, InBackpack>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (player_entity, mut gamelog, mut wants_pickup, mut positions, names, mut backpack) =
+            data;
+
+        for pickup in wants_pickup.join() {
+            // Off the map and into the backpack
+            positions.remove(pickup.item);
+            backpack
+                .insert(
+                    pickup.item,
+                    InBackpack {
+                        owner: pickup.collected_by,
+                    },
+                )
+                .expect("Unable to insert backpack entry");
+
+            if pickup.collected_by == *player_entity {
+                gamelog.entries.push(format!(
+                    "You pick up the {}.",
+                    names.get(pickup.item).unwrap().name
+                ));
+            }
+        }
+
+        // We have processed all pickup requests so clear them
+        wants_pickup.clear();
+    }
+}
+
+pub struct ItemDropSystem {}
+
+impl<'a> System<'a> for ItemDropSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        Entities<'a>,
+        WriteStorage<'a, WantsToDropItem>,
+        ReadStorage<'a, Name>,
+        WriteStorage<'a, GridPosition>,
+        WriteStorage<'a, InBackpack>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            player_entity,
+            mut gamelog,
+            entities,
+            mut wants_drop,
+            names,
+            mut positions,
+            mut backpack,
+        ) = data;
+
+        for (entity, to_drop) in (&entities, &wants_drop).join() {
+            // Drop it where the dropper is standing
+            let dropper_pos = match positions.get(entity) {
+                Some(pos) => GridPosition { x: pos.x, y: pos.y },
+                None => continue,
+            };
+
+            positions
+                .insert(to_drop.item, dropper_pos)
+                .expect("Unable to insert position");
+            backpack.remove(to_drop.item);
+
+            if entity == *player_entity {
+                gamelog.entries.push(format!(
+                    "You drop the {}.",
+                    names.get(to_drop.item).unwrap().name
+                ));
+            }
+        }
+
+        // We have processed all drop requests so clear them
+        wants_drop.clear();
+    }
+}
+
+pub struct ItemUseSystem {}
+
+impl<'a> System<'a> for ItemUseSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        ReadExpect<'a, Map>,
+        Entities<'a>,
+        WriteStorage<'a, WantsToUseItem>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Consumable>,
+        ReadStorage<'a, ProvidesHealing>,
+        ReadStorage<'a, InflictsDamage>,
+        ReadStorage<'a, AreaOfEffect>,
+        WriteStorage<'a, CombatStats>,
+        WriteStorage<'a, SufferDamage>,
+        ReadStorage<'a, Equippable>,
+        WriteStorage<'a, Equipped>,
+        WriteStorage<'a, InBackpack>,
+        ReadStorage<'a, InflictsStatus>,
+        WriteStorage<'a, StatusEffects>,
+        ReadStorage<'a, ProvidesFood>,
+        WriteStorage<'a, HungerClock>,
+        ReadStorage<'a, Attributes>,
+        WriteStorage<'a, Skills>,
+        WriteStorage<'a, Energy>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            player_entity,
+            mut gamelog,
+            map,
+            entities,
+            mut wants_use,
+            names,
+            consumables,
+            healing,
+            inflict_damage,
+            area_of_effect,
+            mut combat_stats,
+            mut suffer_damage,
+            equippable,
+            mut equipped,
+            mut backpack,
+            inflicts_status,
+            mut status_effects,
+            provides_food,
+            mut hunger_clocks,
+            attributes,
+            mut skills,
+            mut energies,
+        ) = data;
+
+        for (entity, useitem) in (&entities, &wants_use).join() {
+            let item_name = &names.get(useitem.item).unwrap().name;
+
+            // Healing restores hp, up to max_hp
+            if let Some(healer) = healing.get(useitem.item) {
+                if let Some(stats) = combat_stats.get_mut(entity) {
+                    let hp_before = stats.hp;
+                    stats.heal(healer.heal_amount);
+
+                    if entity == *player_entity {
+                        gamelog.entries.push(format!(
+                            "You drink the {}, healing {} hp.",
+                            item_name,
+                            stats.hp - hp_before
+                        ));
+                    }
+                }
+            }
+
+            // Food resets the hunger clock
+            if provides_food.get(useitem.item).is_some() {
+                if let Some(clock) = hunger_clocks.get_mut(entity) {
+                    clock.state = HungerState::WellFed;
+                    clock.duration = WELL_FED_DURATION;
+
+                    if entity == *player_entity {
+                        gamelog.entries.push(format!("You eat the {}.", item_name));
+                    }
+                }
+            }
+
+            // Whoever stands on the target tile, or everyone caught in the blast
+            let victims: Vec<Entity> = match useitem.target {
+                None => Vec::new(),
+                Some(target) => match area_of_effect.get(useitem.item) {
+                    Some(aoe) => map.entities_in_blast(target, aoe.radius),
+                    None => {
+                        let idx = map.xy_idx(target.x, target.y);
+                        map.tile_content[idx].clone()
+                    }
+                },
+            };
+
+            if let Some(damage) = inflict_damage.get(useitem.item) {
+                // Magic users get more out of their scrolls
+                let magic_bonus = skills.get(entity).map_or(0, |s| s.magic.level)
+                    + attributes.get(entity).map_or(0, |a| a.intelligence_bonus());
+                let amount = i32::max(1, damage.damage + magic_bonus);
+
+                for mob in victims.iter() {
+                    if combat_stats.get(*mob).is_none() {
+                        continue;
+                    }
+
+                    SufferDamage::new_damage(
+                        &mut suffer_damage,
+                        *mob,
+                        amount,
+                        damage.kind,
+                        Some(entity),
+                    );
+
+                    let mob_name = &names.get(*mob).unwrap().name;
+                    if area_of_effect.get(useitem.item).is_some() {
+                        // Every blast hit gets its own line
+                        gamelog
+                            .entries
+                            .push(format!("The {} engulfs {}.", item_name, mob_name));
+                    } else if entity == *player_entity {
+                        gamelog
+                            .entries
+                            .push(format!("You use {} on {}.", item_name, mob_name));
+                    }
+                }
+
+                practice_skill(
+                    &mut skills,
+                    &mut gamelog,
+                    entity,
+                    *player_entity,
+                    "magic",
+                    |s| &mut s.magic,
+                );
+            }
+
+            if let Some(status) = inflicts_status.get(useitem.item) {
+                // Without a target it's something like a potion, and affects the user
+                let affected = match useitem.target {
+                    None => vec![entity],
+                    Some(_) => victims.clone(),
+                };
+
+                for mob in affected.iter() {
+                    if combat_stats.get(*mob).is_none() {
+                        continue;
+                    }
+
+                    if status_effects.get(*mob).is_none() {
+                        status_effects
+                            .insert(*mob, StatusEffects::default())
+                            .expect("Unable to insert status effects");
+                    }
+
+                    let effects = status_effects.get_mut(*mob).unwrap();
+                    if effects.add(status.kind, status.turns, Some(entity)) {
+                        gamelog.entries.push(format!(
+                            "{} is {}!",
+                            names.get(*mob).unwrap().name,
+                            status.kind.adjective()
+                        ));
+                    }
+                }
+            }
+
+            // Equipping swaps out whatever was already in that slot
+            if let Some(can_equip) = equippable.get(useitem.item) {
+                let target_slot = can_equip.slot;
+
+                let mut to_unequip: Vec<Entity> = Vec::new();
+                for (item_entity, already_equipped, name) in (&entities, &equipped, &names).join() {
+                    if already_equipped.owner == entity && already_equipped.slot == target_slot {
+                        to_unequip.push(item_entity);
+                        if entity == *player_entity {
+                            gamelog
+                                .entries
+                                .push(format!("You unequip the {}.", name.name));
+                        }
+                    }
+                }
+                for item in to_unequip.iter() {
+                    equipped.remove(*item);
+                    backpack
+                        .insert(*item, InBackpack { owner: entity })
+                        .expect("Unable to insert backpack entry");
+                }
+
+                equipped
+                    .insert(
+                        useitem.item,
+                        Equipped {
+                            owner: entity,
+                            slot: target_slot,
+                        },
+                    )
+                    .expect("Unable to equip desired item");
+                backpack.remove(useitem.item);
+
+                if entity == *player_entity {
+                    gamelog
+                        .entries
+                        .push(format!("You equip the {}.", item_name));
+                }
+
+                // Strapping on armor takes an extra turn
+                if target_slot == EquipmentSlot::Armor {
+                    if let Some(energy) = energies.get_mut(entity) {
+                        energy.current -= ACTION_COST;
+                    }
+                }
+            }
+
+            // Consumables are gone once used
+            if consumables.get(useitem.item).is_some() {
+                entities
+                    .delete(useitem.item)
+                    .expect("Delete consumed item failed");
+            }
+        }
+
+        // We have processed all use requests so clear them
+        wants_use.clear();
+    }
+}
+
+pub struct ItemRemoveSystem {}
+
+impl<'a> System<'a> for ItemRemoveSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        Entities<'a>,
+        WriteStorage<'a, WantsToRemoveItem>,
+        ReadStorage<'a, Name>,
+        WriteStorage<'a, Equipped>,
+        WriteStorage<'a, InBackpack>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            player_entity,
+            mut gamelog,
+            entities,
+            mut wants_remove,
+            names,
+            mut equipped,
+            mut backpack,
+        ) = data;
+
+        for (entity, to_remove) in (&entities, &wants_remove).join() {
+            // Back into the backpack
+            equipped.remove(to_remove.item);
+            backpack
+                .insert(to_remove.item, InBackpack { owner: entity })
+                .expect("Unable to insert backpack entry");
+
+            if entity == *player_entity {
+                gamelog.entries.push(format!(
+                    "You unequip the {}.",
+                    names.get(to_remove.item).unwrap().name
+                ));
+            }
+        }
+
+        // We have processed all remove requests so clear them
+        wants_remove.clear();
+    }
+}
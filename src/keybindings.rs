@@ -0,0 +1,283 @@
+use ggez::event::KeyCode;
+use ggez::{Context, GameResult};
+
+use std::collections::{BTreeMap, HashMap};
+
+use super::config_file;
+
+/// Path (inside ggez's resource dirs) of the user-editable bindings file
+pub const KEYBINDINGS_FILE: &str = "/keybindings.cfg";
+
+/// Everything the player can ask for from the keyboard
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Debug)]
+pub enum Action {
+    MoveNorth,
+    MoveSouth,
+    MoveEast,
+    MoveWest,
+    MoveNorthEast,
+    MoveNorthWest,
+    MoveSouthEast,
+    MoveSouthWest,
+    Wait,
+    Rest,
+    PickUp,
+    ShowInventory,
+    DropItem,
+    RemoveItem,
+    Descend,
+    OpenMenu,
+    ToggleDebug,
+}
+
+impl Action {
+    /// Grid step for the movement actions
+    pub fn direction(self) -> Option<(i32, i32)> {
+        match self {
+            Action::MoveNorth => Some((0, -1)),
+            Action::MoveSouth => Some((0, 1)),
+            Action::MoveEast => Some((1, 0)),
+            Action::MoveWest => Some((-1, 0)),
+            Action::MoveNorthEast => Some((1, -1)),
+            Action::MoveNorthWest => Some((-1, -1)),
+            Action::MoveSouthEast => Some((1, 1)),
+            Action::MoveSouthWest => Some((-1, 1)),
+            _ => None,
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Action> {
+        match name {
+            "move_north" => Some(Action::MoveNorth),
+            "move_south" => Some(Action::MoveSouth),
+            "move_east" => Some(Action::MoveEast),
+            "move_west" => Some(Action::MoveWest),
+            "move_north_east" => Some(Action::MoveNorthEast),
+            "move_north_west" => Some(Action::MoveNorthWest),
+            "move_south_east" => Some(Action::MoveSouthEast),
+            "move_south_west" => Some(Action::MoveSouthWest),
+            "wait" => Some(Action::Wait),
+            "rest" => Some(Action::Rest),
+            "pick_up" => Some(Action::PickUp),
+            "inventory" => Some(Action::ShowInventory),
+            "drop" => Some(Action::DropItem),
+            "remove" => Some(Action::RemoveItem),
+            "descend" => Some(Action::Descend),
+            "menu" => Some(Action::OpenMenu),
+            "debug" => Some(Action::ToggleDebug),
+            _ => None,
+        }
+    }
+}
+
+/// Maps pressed keys to actions. Several keys may trigger the same action,
+/// but a key can only ever be bound to a single action.
+pub struct KeyBindings {
+    bindings: HashMap<KeyCode, Action>,
+}
+
+impl KeyBindings {
+    /// Loads the bindings file from the ggez filesystem
+    pub fn load(ctx: &mut Context) -> GameResult<KeyBindings> {
+        let contents = config_file::read(ctx, KEYBINDINGS_FILE)?;
+        KeyBindings::parse(&contents)
+    }
+
+    /// Parses lines of the form `action = Key, Key, ...`
+    pub fn parse(contents: &str) -> GameResult<KeyBindings> {
+        let mut bindings: HashMap<KeyCode, Action> = HashMap::new();
+
+        for line in config_file::lines(KEYBINDINGS_FILE, contents, '=', "action = Key, ...")? {
+            let action = Action::from_name(line.head)
+                .ok_or_else(|| line.error(format!("unknown action `{}`", line.head)))?;
+
+            for key_name in config_file::list(line.rest) {
+                let key = key_from_name(key_name)
+                    .ok_or_else(|| line.error(format!("unknown key `{}`", key_name)))?;
+
+                // Conflict detection: the same key can't trigger two different actions
+                match bindings.get(&key) {
+                    Some(existing) if *existing != action => {
+                        return Err(line.error(format!(
+                            "key `{}` is bound to both {:?} and {:?}",
+                            key_name, existing, action
+                        )));
+                    }
+                    _ => {
+                        bindings.insert(key, action);
+                    }
+                }
+            }
+        }
+
+        Ok(KeyBindings { bindings })
+    }
+
+    pub fn action_for(&self, key: KeyCode) -> Option<Action> {
+        self.bindings.get(&key).copied()
+    }
+
+    /// One human readable line per action, listing the keys bound to it
+    pub fn describe(&self) -> Vec<String> {
+        let mut by_action: BTreeMap<Action, Vec<String>> = BTreeMap::new();
+        for (key, action) in self.bindings.iter() {
+            by_action
+                .entry(*action)
+                .or_default()
+                .push(format!("{:?}", key));
+        }
+
+        by_action
+            .iter_mut()
+            .map(|(action, keys)| {
+                keys.sort();
+                format!("{:?}: {}", action, keys.join(", "))
+            })
+            .collect()
+    }
+}
+
+// @TODO: Not every KeyCode is listed, just the ones that make sense for bindings
+fn key_from_name(name: &str) -> Option<KeyCode> {
+    let key = match name {
+        "Key1" => KeyCode::Key1,
+        "Key2" => KeyCode::Key2,
+        "Key3" => KeyCode::Key3,
+        "Key4" => KeyCode::Key4,
+        "Key5" => KeyCode::Key5,
+        "Key6" => KeyCode::Key6,
+        "Key7" => KeyCode::Key7,
+        "Key8" => KeyCode::Key8,
+        "Key9" => KeyCode::Key9,
+        "Key0" => KeyCode::Key0,
+        "A" => KeyCode::A,
+        "B" => KeyCode::B,
+        "C" => KeyCode::C,
+        "D" => KeyCode::D,
+        "E" => KeyCode::E,
+        "F" => KeyCode::F,
+        "G" => KeyCode::G,
+        "H" => KeyCode::H,
+        "I" => KeyCode::I,
+        "J" => KeyCode::J,
+        "K" => KeyCode::K,
+        "L" => KeyCode::L,
+        "M" => KeyCode::M,
+        "N" => KeyCode::N,
+        "O" => KeyCode::O,
+        "P" => KeyCode::P,
+        "Q" => KeyCode::Q,
+        "R" => KeyCode::R,
+        "S" => KeyCode::S,
+        "T" => KeyCode::T,
+        "U" => KeyCode::U,
+        "V" => KeyCode::V,
+        "W" => KeyCode::W,
+        "X" => KeyCode::X,
+        "Y" => KeyCode::Y,
+        "Z" => KeyCode::Z,
+        "F1" => KeyCode::F1,
+        "F2" => KeyCode::F2,
+        "F3" => KeyCode::F3,
+        "F4" => KeyCode::F4,
+        "F5" => KeyCode::F5,
+        "F6" => KeyCode::F6,
+        "F7" => KeyCode::F7,
+        "F8" => KeyCode::F8,
+        "F9" => KeyCode::F9,
+        "F10" => KeyCode::F10,
+        "F11" => KeyCode::F11,
+        "F12" => KeyCode::F12,
+        "Left" => KeyCode::Left,
+        "Right" => KeyCode::Right,
+        "Up" => KeyCode::Up,
+        "Down" => KeyCode::Down,
+        "Home" => KeyCode::Home,
+        "End" => KeyCode::End,
+        "PageUp" => KeyCode::PageUp,
+        "PageDown" => KeyCode::PageDown,
+        "Insert" => KeyCode::Insert,
+        "Delete" => KeyCode::Delete,
+        "Back" => KeyCode::Back,
+        "Return" => KeyCode::Return,
+        "Space" => KeyCode::Space,
+        "Tab" => KeyCode::Tab,
+        "Escape" => KeyCode::Escape,
+        "Numpad0" => KeyCode::Numpad0,
+        "Numpad1" => KeyCode::Numpad1,
+        "Numpad2" => KeyCode::Numpad2,
+        "Numpad3" => KeyCode::Numpad3,
+        "Numpad4" => KeyCode::Numpad4,
+        "Numpad5" => KeyCode::Numpad5,
+        "Numpad6" => KeyCode::Numpad6,
+        "Numpad7" => KeyCode::Numpad7,
+        "Numpad8" => KeyCode::Numpad8,
+        "Numpad9" => KeyCode::Numpad9,
+        "NumpadEnter" => KeyCode::NumpadEnter,
+        "Add" => KeyCode::Add,
+        "Subtract" => KeyCode::Subtract,
+        "Multiply" => KeyCode::Multiply,
+        "Divide" => KeyCode::Divide,
+        "Decimal" => KeyCode::Decimal,
+        "Apostrophe" => KeyCode::Apostrophe,
+        "Backslash" => KeyCode::Backslash,
+        "Comma" => KeyCode::Comma,
+        "Equals" => KeyCode::Equals,
+        "Grave" => KeyCode::Grave,
+        "LBracket" => KeyCode::LBracket,
+        "RBracket" => KeyCode::RBracket,
+        "Minus" => KeyCode::Minus,
+        "Period" => KeyCode::Period,
+        "Semicolon" => KeyCode::Semicolon,
+        "Slash" => KeyCode::Slash,
+        _ => return None,
+    };
+
+    Some(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_actions_with_several_keys() {
+        let bindings = KeyBindings::parse("move_west = Left, Numpad4, H\nwait = Space").unwrap();
+
+        assert_eq!(bindings.action_for(KeyCode::Left), Some(Action::MoveWest));
+        assert_eq!(
+            bindings.action_for(KeyCode::Numpad4),
+            Some(Action::MoveWest)
+        );
+        assert_eq!(bindings.action_for(KeyCode::H), Some(Action::MoveWest));
+        assert_eq!(bindings.action_for(KeyCode::Space), Some(Action::Wait));
+        assert_eq!(bindings.action_for(KeyCode::Right), None);
+    }
+
+    #[test]
+    fn rejects_a_key_bound_to_two_actions() {
+        assert!(KeyBindings::parse("move_west = H\nmove_east = H").is_err());
+    }
+
+    #[test]
+    fn allows_a_key_repeated_for_the_same_action() {
+        let bindings = KeyBindings::parse("wait = Space, Space\nwait = Space").unwrap();
+
+        assert_eq!(bindings.action_for(KeyCode::Space), Some(Action::Wait));
+    }
+
+    #[test]
+    fn rejects_bad_lines() {
+        assert!(KeyBindings::parse("fly = F").is_err());
+        assert!(KeyBindings::parse("wait = NotAKey").is_err());
+    }
+
+    #[test]
+    fn default_bindings_file_parses() {
+        let bindings = KeyBindings::parse(include_str!("../resources/keybindings.cfg")).unwrap();
+
+        // Same diagonals as before the bindings file existed
+        assert_eq!(bindings.action_for(KeyCode::Y), Some(Action::MoveNorthEast));
+        assert_eq!(bindings.action_for(KeyCode::U), Some(Action::MoveNorthWest));
+    }
+}
@@ -7,6 +7,8 @@ extern crate specs_derive;
 
 use ggez;
 use ggez::event;
+use ggez::event::{KeyCode, KeyMods};
+use ggez::input::mouse::MouseButton;
 use ggez::graphics as gfx;
 use ggez::nalgebra as na;
 use ggez::timer;
@@ -18,6 +20,13 @@ mod rect32;
 pub use rect32::Rect32;
 mod player;
 use player::*;
+mod config_file;
+mod keybindings;
+use keybindings::KeyBindings;
+mod factions;
+use factions::FactionTable;
+mod ranged_attacks;
+use ranged_attacks::RangedAttackTable;
 mod map;
 pub use map::*;
 
@@ -31,6 +40,24 @@ mod melee_combat_system;
 use melee_combat_system::MeleeCombatSystem;
 mod damage_system;
 use damage_system::DamageSystem;
+mod regen_system;
+use regen_system::RegenSystem;
+mod status_effect_system;
+use status_effect_system::StatusEffectSystem;
+mod hunger_system;
+use hunger_system::HungerSystem;
+mod initiative_system;
+use initiative_system::{GameClock, InitiativeSystem};
+mod hearing_system;
+use hearing_system::HearingSystem;
+mod scent_system;
+use scent_system::{ScentFadeSystem, ScentSystem};
+mod ranged_combat_system;
+use ranged_combat_system::RangedCombatSystem;
+mod pack_system;
+use pack_system::{PackSystem, Packs};
+mod inventory_system;
+use inventory_system::{ItemCollectionSystem, ItemDropSystem, ItemRemoveSystem, ItemUseSystem};
 
 // mod imgui_wrapper;
 // use imgui_wrapper::ImGuiWrapper;
@@ -38,6 +65,12 @@ mod gg_gui;
 use gg_gui as gui;
 
 mod gamelog;
+mod gamestats;
+mod spawner;
+mod menu;
+use menu::{ItemMenuResult, MainMenuResult, MainMenuSelection};
+mod targeting;
+use targeting::TargetingResult;
 
 // GAME STATE
 
@@ -47,10 +80,36 @@ pub enum RunState {
     PreRun,
     PlayerTurn,
     MonsterTurn,
+    /// Energy is building up until someone gets to act
+    Ticking,
+    GameOver,
+    MainMenu { menu_selection: MainMenuSelection },
+    Options,
+    ShowInventory,
+    ShowDropItem,
+    ShowRemoveItem,
+    ShowTargeting {
+        range: i32,
+        item: Entity,
+        cursor: rltk::Point,
+    },
 }
 
 pub struct State {
     pub ecs: World,
+    pub keybindings: KeyBindings,
+    pub factions: FactionTable,
+    pub ranged_attacks: RangedAttackTable,
+    /// Last key pressed since the previous update, consumed by the input handling
+    pub key: Option<KeyCode>,
+    /// Mouse events since the previous update, consumed like `key`
+    pub mouse_clicked: bool,
+    pub mouse_moved: bool,
+    /// True once a game has been set up, so there's something to go back to from the menu
+    pub game_in_progress: bool,
+    pub title_image: rltk::rex::XpFile,
+    /// Shows extra info on screen, like what every monster is thinking
+    pub show_debug: bool,
     // imgui_wrapper: ImGuiWrapper,
 }
 
@@ -60,6 +119,14 @@ impl State {
         let mut vis = VisibilitySystem {};
         vis.run_now(&self.ecs);
 
+        // Lay down the player's scent
+        let mut scent = ScentSystem {};
+        scent.run_now(&self.ecs);
+
+        // Let packs share what they know before their members act
+        let mut packs = PackSystem {};
+        packs.run_now(&self.ecs);
+
         // Run monster AI system
         let mut mob = MonsterAISystem {};
         mob.run_now(&self.ecs);
@@ -68,9 +135,25 @@ impl State {
         let mut mapindex = MapIndexingSystem {};
         mapindex.run_now(&self.ecs);
 
-        // Run the melee combat system
+        // Run the inventory systems
+        let mut pickup = ItemCollectionSystem {};
+        pickup.run_now(&self.ecs);
+        let mut use_items = ItemUseSystem {};
+        use_items.run_now(&self.ecs);
+        let mut drop_items = ItemDropSystem {};
+        drop_items.run_now(&self.ecs);
+        let mut remove_items = ItemRemoveSystem {};
+        remove_items.run_now(&self.ecs);
+
+        // Run the melee and ranged combat systems
         let mut melee = MeleeCombatSystem {};
         melee.run_now(&self.ecs);
+        let mut ranged = RangedCombatSystem {};
+        ranged.run_now(&self.ecs);
+
+        // Let monsters hear the noise made this turn
+        let mut hearing = HearingSystem {};
+        hearing.run_now(&self.ecs);
 
         // Run damage system
         let mut damage = DamageSystem {};
@@ -79,6 +162,138 @@ impl State {
         // Update world after running systems
         self.ecs.maintain();
     }
+
+    /// Systems that follow the game clock, once per turn of game time,
+    /// however often anyone gets to act
+    fn run_clock_systems(&mut self) {
+        // Tick status effects
+        let mut status = StatusEffectSystem {};
+        status.run_now(&self.ecs);
+
+        // Run the hunger clock
+        let mut hunger = HungerSystem {};
+        hunger.run_now(&self.ecs);
+
+        // Fade the scents left behind
+        let mut scent_fade = ScentFadeSystem {};
+        scent_fade.run_now(&self.ecs);
+
+        // Run the health regeneration system
+        let mut regen = RegenSystem {};
+        regen.run_now(&self.ecs);
+
+        // Poison and starvation hurt
+        let mut damage = DamageSystem {};
+        damage.run_now(&self.ecs);
+
+        self.ecs.maintain();
+    }
+
+    /// Who got a turn on the last tick: (the player, any monster)
+    fn ready_to_act(&self) -> (bool, bool) {
+        let player_entity = *self.ecs.fetch::<Entity>();
+        let turns = self.ecs.read_storage::<MyTurn>();
+        let monsters = self.ecs.read_storage::<Monster>();
+
+        let player_ready = turns.get(player_entity).is_some();
+        let monsters_ready = (&turns, &monsters).join().next().is_some();
+        (player_ready, monsters_ready)
+    }
+
+    /// Throws away the current world (if any) and sets up a brand new game
+    fn new_game(&mut self) {
+        self.ecs = World::new();
+        self.game_in_progress = true;
+
+        // Register components
+        self.ecs.register::<GridPosition>();
+        self.ecs.register::<Renderable>();
+        self.ecs.register::<Player>();
+        self.ecs.register::<Viewshed>();
+        self.ecs.register::<Monster>();
+        self.ecs.register::<Name>();
+        self.ecs.register::<BlocksTile>();
+        self.ecs.register::<CombatStats>();
+        self.ecs.register::<WantsToMelee>();
+        self.ecs.register::<SufferDamage>();
+        self.ecs.register::<KilledBy>();
+        self.ecs.register::<HealthRegen>();
+        self.ecs.register::<Resting>();
+        self.ecs.register::<Item>();
+        self.ecs.register::<InBackpack>();
+        self.ecs.register::<WantsToPickupItem>();
+        self.ecs.register::<WantsToDropItem>();
+        self.ecs.register::<Consumable>();
+        self.ecs.register::<ProvidesHealing>();
+        self.ecs.register::<WantsToUseItem>();
+        self.ecs.register::<Ranged>();
+        self.ecs.register::<InflictsDamage>();
+        self.ecs.register::<AreaOfEffect>();
+        self.ecs.register::<Equippable>();
+        self.ecs.register::<Equipped>();
+        self.ecs.register::<MeleePowerBonus>();
+        self.ecs.register::<DefenseBonus>();
+        self.ecs.register::<WantsToRemoveItem>();
+        self.ecs.register::<StatusEffects>();
+        self.ecs.register::<InflictsStatus>();
+        self.ecs.register::<HungerClock>();
+        self.ecs.register::<ProvidesFood>();
+        self.ecs.register::<Experience>();
+        self.ecs.register::<GrantsXp>();
+        self.ecs.register::<Attributes>();
+        self.ecs.register::<Skills>();
+        self.ecs.register::<MeleeDamage>();
+        self.ecs.register::<Resistant>();
+        self.ecs.register::<Vulnerable>();
+        self.ecs.register::<Corpse>();
+        self.ecs.register::<LootTable>();
+        self.ecs.register::<Energy>();
+        self.ecs.register::<MyTurn>();
+        self.ecs.register::<Brain>();
+        self.ecs.register::<Faction>();
+        self.ecs.register::<Follower>();
+        self.ecs.register::<Noise>();
+        self.ecs.register::<Asleep>();
+        self.ecs.register::<Tracker>();
+        self.ecs.register::<RangedAttack>();
+        self.ecs.register::<WantsToShoot>();
+        self.ecs.register::<PackMember>();
+
+        // Shared random number generator
+        self.ecs.insert(rltk::RandomNumberGenerator::new());
+        self.ecs.insert(self.factions.clone());
+        self.ecs.insert(self.ranged_attacks.clone());
+        self.ecs.insert(Packs::default());
+        self.ecs.insert(GameClock::default());
+
+        // Add a map to ECS resources
+        // and place player in the center of 1st room
+        let map: Map = Map::new_map_rooms_and_corridors();
+        let (player_x, player_y) = map.rooms[0].center();
+
+        // Create player
+        let player_entity = spawner::player(&mut self.ecs, player_x, player_y);
+        // And their faithful companion, right next to them
+        spawner::companion(&mut self.ecs, player_x + 1, player_y);
+
+        // Add some monsters and items
+        for (i, room) in map.rooms.iter().skip(1).enumerate() {
+            spawner::spawn_room(&mut self.ecs, room, i);
+        }
+
+        // INSERT RESOURCES
+        self.ecs.insert(map);
+        self.ecs.insert(rltk::Point::new(player_x, player_y)); // @TODO: Should this be an rltk::Point or something else?
+        self.ecs.insert(player_entity);
+        self.ecs.insert(RunState::PreRun);
+        self.ecs.insert(gamelog::GameLog {
+            entries: vec!["Let's begin hero!!".to_string()],
+        });
+        self.ecs.insert(gamestats::GameStats {
+            turns: 0,
+            cause_of_death: None,
+        });
+    }
 }
 
 impl event::EventHandler for State {
@@ -102,17 +317,177 @@ impl event::EventHandler for State {
                 newrunstate = RunState::AwaitingInput;
             },
             RunState::AwaitingInput => {
-                // Any new state change is done in player input
-                newrunstate = player_input(self, ctx);
+                // Stuns and resting pass turns on their own,
+                // any other state change is done in player input
+                let automatic_turn = skip_turn_if_stunned(self).or_else(|| continue_resting(self));
+                newrunstate = match automatic_turn {
+                    Some(state) => state,
+                    None => player_input(self),
+                };
 
             },
             RunState::PlayerTurn => {
+                self.ecs.fetch_mut::<gamestats::GameStats>().turns += 1;
                 self.run_systems();
-                newrunstate = RunState::MonsterTurn;
+                newrunstate = RunState::Ticking;
             },
             RunState::MonsterTurn => {
+                // Monsters with a turn act. The player may have got a turn on the same tick.
                 self.run_systems();
-                newrunstate = RunState::AwaitingInput;
+                let (player_ready, _) = self.ready_to_act();
+                newrunstate = if player_ready {
+                    RunState::AwaitingInput
+                } else {
+                    RunState::Ticking
+                };
+            }
+            RunState::Ticking => {
+                // Hand out energy until someone can act. Monsters go first on a shared tick.
+                loop {
+                    let mut initiative = InitiativeSystem {};
+                    initiative.run_now(&self.ecs);
+                    self.ecs.maintain();
+
+                    if self.ecs.fetch::<GameClock>().turn_passed() {
+                        self.run_clock_systems();
+                        // Nobody gets to act after dying of poison or hunger
+                        damage_system::delete_the_dead(&mut self.ecs);
+                        if *self.ecs.fetch::<RunState>() == RunState::GameOver {
+                            newrunstate = RunState::GameOver;
+                            break;
+                        }
+                    }
+
+                    let (player_ready, monsters_ready) = self.ready_to_act();
+                    if monsters_ready {
+                        newrunstate = RunState::MonsterTurn;
+                        break;
+                    }
+                    if player_ready {
+                        newrunstate = RunState::AwaitingInput;
+                        break;
+                    }
+                }
+            }
+            RunState::GameOver => {
+                // Any key starts a fresh game
+                if self.key.is_some() {
+                    self.new_game();
+                    newrunstate = RunState::PreRun;
+                }
+            }
+            RunState::MainMenu { menu_selection } => {
+                match menu::main_menu_input(self, ctx, menu_selection) {
+                    MainMenuResult::NoSelection { selected } => {
+                        newrunstate = RunState::MainMenu {
+                            menu_selection: selected,
+                        };
+                    }
+                    MainMenuResult::Selected { selected } => match selected {
+                        MainMenuSelection::NewGame => {
+                            self.new_game();
+                            newrunstate = RunState::PreRun;
+                        }
+                        MainMenuSelection::Options => newrunstate = RunState::Options,
+                        MainMenuSelection::Quit => event::quit(ctx),
+                    },
+                    MainMenuResult::Back => newrunstate = RunState::AwaitingInput,
+                }
+            }
+            RunState::ShowInventory => {
+                let player_entity = *self.ecs.fetch::<Entity>();
+                let items = menu::backpack_items(&self.ecs, player_entity);
+                match menu::item_menu_input(self, &items) {
+                    ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    ItemMenuResult::NoResponse => {}
+                    ItemMenuResult::Selected(item) => {
+                        let ranged = self.ecs.read_storage::<Ranged>().get(item).map(|r| r.range);
+                        match ranged {
+                            // Ranged items need a target first
+                            Some(range) => {
+                                newrunstate = RunState::ShowTargeting {
+                                    range,
+                                    item,
+                                    cursor: *self.ecs.fetch::<rltk::Point>(),
+                                };
+                            }
+                            None => {
+                                // Using an item takes up the player's turn
+                                let mut intent = self.ecs.write_storage::<WantsToUseItem>();
+                                intent
+                                    .insert(player_entity, WantsToUseItem { item, target: None })
+                                    .expect("Unable to insert intent");
+                                newrunstate = RunState::PlayerTurn;
+                            }
+                        }
+                    }
+                }
+            }
+            RunState::ShowDropItem => {
+                let player_entity = *self.ecs.fetch::<Entity>();
+                let items = menu::backpack_items(&self.ecs, player_entity);
+                match menu::item_menu_input(self, &items) {
+                    ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    ItemMenuResult::NoResponse => {}
+                    ItemMenuResult::Selected(item) => {
+                        let mut intent = self.ecs.write_storage::<WantsToDropItem>();
+                        intent
+                            .insert(player_entity, WantsToDropItem { item })
+                            .expect("Unable to insert intent");
+                        newrunstate = RunState::PlayerTurn;
+                    }
+                }
+            }
+            RunState::ShowRemoveItem => {
+                let player_entity = *self.ecs.fetch::<Entity>();
+                let items = menu::equipped_items(&self.ecs, player_entity);
+                match menu::item_menu_input(self, &items) {
+                    ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    ItemMenuResult::NoResponse => {}
+                    ItemMenuResult::Selected(item) => {
+                        let mut intent = self.ecs.write_storage::<WantsToRemoveItem>();
+                        intent
+                            .insert(player_entity, WantsToRemoveItem { item })
+                            .expect("Unable to insert intent");
+                        newrunstate = RunState::PlayerTurn;
+                    }
+                }
+            }
+            RunState::ShowTargeting {
+                range,
+                item,
+                cursor,
+            } => match targeting::targeting_input(self, ctx, range, cursor) {
+                TargetingResult::Cancel => newrunstate = RunState::AwaitingInput,
+                TargetingResult::NoResponse { cursor } => {
+                    newrunstate = RunState::ShowTargeting {
+                        range,
+                        item,
+                        cursor,
+                    };
+                }
+                TargetingResult::Selected(target) => {
+                    let player_entity = *self.ecs.fetch::<Entity>();
+                    let mut intent = self.ecs.write_storage::<WantsToUseItem>();
+                    intent
+                        .insert(
+                            player_entity,
+                            WantsToUseItem {
+                                item,
+                                target: Some(target),
+                            },
+                        )
+                        .expect("Unable to insert intent");
+                    newrunstate = RunState::PlayerTurn;
+                }
+            },
+            RunState::Options => {
+                // Any key or click goes back to the main menu
+                if self.key.is_some() || self.mouse_clicked {
+                    newrunstate = RunState::MainMenu {
+                        menu_selection: MainMenuSelection::Options,
+                    };
+                }
             }
         }
 
@@ -133,28 +508,89 @@ impl event::EventHandler for State {
         // }
 
         // Delete dead entities
-        damage_system::delete_the_dead(&mut self.ecs);
+        if self.game_in_progress {
+            damage_system::delete_the_dead(&mut self.ecs);
+        }
+
+        // Input events are only valid for the update right after they happened
+        self.key = None;
+        self.mouse_clicked = false;
+        self.mouse_moved = false;
 
         Ok(())
     }
 
+    fn key_down_event(
+        &mut self,
+        _ctx: &mut Context,
+        keycode: KeyCode,
+        _keymods: KeyMods,
+        _repeat: bool,
+    ) {
+        self.key = Some(keycode);
+    }
+
+    fn mouse_button_down_event(
+        &mut self,
+        _ctx: &mut Context,
+        button: MouseButton,
+        _x: f32,
+        _y: f32,
+    ) {
+        if button == MouseButton::Left {
+            self.mouse_clicked = true;
+        }
+    }
+
+    fn mouse_motion_event(&mut self, _ctx: &mut Context, _x: f32, _y: f32, _dx: f32, _dy: f32) {
+        self.mouse_moved = true;
+    }
+
     fn draw(&mut self, ctx: &mut Context) -> GameResult {
         gfx::clear(ctx, [0.1, 0.2, 0.3, 1.0].into());
 
+        // Menus replace the game screen entirely
+        let runstate = *self.ecs.fetch::<RunState>();
+        match runstate {
+            RunState::MainMenu { menu_selection } => {
+                gui::draw_main_menu(ctx, &self.title_image, menu_selection, self.game_in_progress)?;
+                gfx::present(ctx)?;
+                return Ok(());
+            }
+            RunState::Options => {
+                gui::draw_options(ctx, &self.keybindings)?;
+                gfx::present(ctx)?;
+                return Ok(());
+            }
+            _ => {}
+        }
+
         // Render our map
         draw_map(&self.ecs, ctx)?;
 
         // RENDER MONSTERS
         let positions = self.ecs.read_storage::<GridPosition>();
         let renderables = self.ecs.read_storage::<Renderable>();
+        let asleep = self.ecs.read_storage::<Asleep>();
+        let entities = self.ecs.entities();
         let map = self.ecs.fetch::<Map>();
 
-        for (pos, render) in (&positions, &renderables).join() {
+        // Sort by render order so e.g. items on the floor don't hide monsters
+        let mut data = (&entities, &positions, &renderables).join().collect::<Vec<_>>();
+        data.sort_by_key(|(_, _, render)| std::cmp::Reverse(render.render_order));
+
+        for (entity, pos, render) in data.iter() {
             let idx = map.xy_idx(pos.x, pos.y);
             if map.visible_tiles[idx] {
+                // Sleeping monsters are drawn hollow
+                let mode = if asleep.get(*entity).is_some() {
+                    gfx::DrawMode::stroke(2.0)
+                } else {
+                    gfx::DrawMode::fill()
+                };
                 let circle = gfx::Mesh::new_circle(
                     ctx,
-                    gfx::DrawMode::fill(),
+                    mode,
                     na::Point2::new(0.0, 0.0),
                     10.0,
                     2.0,
@@ -174,6 +610,31 @@ impl event::EventHandler for State {
 
         // Render GUI
         gui::draw_ui(&self.ecs, ctx)?;
+        if self.show_debug {
+            gui::draw_ai_debug(&self.ecs, ctx)?;
+        }
+
+        // Menus and the death screen go on top of everything else
+        let player_entity = *self.ecs.fetch::<Entity>();
+        match runstate {
+            RunState::ShowInventory => {
+                let items = menu::backpack_items(&self.ecs, player_entity);
+                gui::draw_item_menu(ctx, "Use which item?", &items)?
+            }
+            RunState::ShowDropItem => {
+                let items = menu::backpack_items(&self.ecs, player_entity);
+                gui::draw_item_menu(ctx, "Drop which item?", &items)?
+            }
+            RunState::ShowRemoveItem => {
+                let items = menu::equipped_items(&self.ecs, player_entity);
+                gui::draw_item_menu(ctx, "Remove which item?", &items)?
+            }
+            RunState::ShowTargeting { range, cursor, .. } => {
+                gui::draw_targeting(&self.ecs, ctx, range, cursor)?
+            }
+            RunState::GameOver => gui::draw_game_over(&self.ecs, ctx)?,
+            _ => {}
+        }
 
         gfx::present(ctx)?;
         Ok(())
@@ -184,111 +645,37 @@ fn main() -> GameResult {
     // @TODO: Screen dims to use for (80 x 50 , tile size 16) = 1280 x 800
     let cb = ggez::ContextBuilder::new("THAT GAME - super simple", "Spiros Makris");
     let (ctx, event_loop) = &mut cb.build()?;
-    
+
+    // Load the user's key bindings
+    let keybindings = KeyBindings::load(ctx)?;
+    // And who fights whom
+    let factions = FactionTable::load(ctx)?;
+    // And what monsters can shoot or cast
+    let ranged_attacks = RangedAttackTable::load(ctx)?;
+
+    // Title screen graphic
+    let mut title_file = ggez::filesystem::open(ctx, "/nyan.xp")?;
+    let title_image = rltk::rex::XpFile::read(&mut title_file)?;
+
     // Create State with ECS world in it.
     let mut gs = State {
         ecs: World::new(),
+        keybindings,
+        factions,
+        ranged_attacks,
+        key: None,
+        mouse_clicked: false,
+        mouse_moved: false,
+        game_in_progress: false,
+        title_image,
+        show_debug: false,
         // imgui_wrapper: ImGuiWrapper::new(&mut ctx) ,
     };
 
-    // Register components
-    gs.ecs.register::<GridPosition>();
-    gs.ecs.register::<Renderable>();
-    gs.ecs.register::<Player>();
-    gs.ecs.register::<Viewshed>();
-    gs.ecs.register::<Monster>();
-    gs.ecs.register::<Name>();
-    gs.ecs.register::<BlocksTile>();
-    gs.ecs.register::<CombatStats>();
-    gs.ecs.register::<WantsToMelee>();
-    gs.ecs.register::<SufferDamage>();
-
-    // Add a map to ECS resources
-    // and placelace player in the center of 1st room
-    let map: Map = Map::new_map_rooms_and_corridors();
-    let (player_x, player_y) = map.rooms[0].center();
-
-    // Create player
-    let player_entity = gs
-        .ecs
-        .create_entity()
-        .with(GridPosition {
-            x: player_x,
-            y: player_y,
-        })
-        .with(Renderable {
-            color: gfx::Color::new(0., 1., 0., 1.),
-        })
-        .with(Player {})
-        .with(Viewshed {
-            visible_tiles: Vec::new(),
-            range: 8,
-            dirty: true,
-        })
-        .with(Name {
-            name: "Player".to_string(),
-        })
-        .with(CombatStats {
-            max_hp: 30,
-            hp: 30,
-            defense: 2,
-            power: 5,
-        })
-        .build();
-
-    // Add some monsters
-    let mut rng = rltk::RandomNumberGenerator::new();
-
-    for (i, room) in map.rooms.iter().skip(1).enumerate() {
-        let (x, y) = room.center();
-
-        let color: gfx::Color;
-        let name: String;
-
-        let roll = rng.roll_dice(1, 2);
-
-        match roll {
-            1 => {
-                color = gfx::Color::new(1.0, 0.0, 0.75, 1.0);
-                name = "Goblin".to_string();
-            }
-
-            _ => {
-                color = gfx::Color::new(1.0, 0.0, 0.1, 1.0);
-                name = "Orc".to_string();
-            }
-        }
-
-        gs.ecs
-            .create_entity()
-            .with(GridPosition { x, y })
-            .with(Renderable { color })
-            .with(Viewshed {
-                visible_tiles: Vec::new(),
-                range: 8,
-                dirty: true,
-            })
-            .with(Monster {})
-            .with(Name {
-                name: format!("{} #{}", &name, i),
-            })
-            .with(BlocksTile {})
-            .with(CombatStats {
-                max_hp: 16,
-                hp: 16,
-                defense: 1,
-                power: 4,
-            })
-            .build();
-    }
-
-    // INSERT RESOURCES
-    gs.ecs.insert(map);
-    gs.ecs.insert(rltk::Point::new(player_x, player_y)); // @TODO: Should this be an rltk::Point or something else?
-    gs.ecs.insert(player_entity);
-    gs.ecs.insert(RunState::PreRun);
-    gs.ecs.insert(gamelog::GameLog{ entries : vec!["Let's begin hero!!".to_string()] });
-
+    // Start at the main menu, the game world is only set up once a new game is picked
+    gs.ecs.insert(RunState::MainMenu {
+        menu_selection: MainMenuSelection::NewGame,
+    });
 
     event::run(ctx, event_loop, &mut gs)
 }
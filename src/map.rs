@@ -1,10 +1,12 @@
 use ggez::graphics as gfx;
 use ggez::{Context, GameResult};
 use std::cmp::{max, min};
+use std::collections::VecDeque;
 extern crate specs;
 use specs::prelude::*;
 
 use super::Rect32;
+use rltk::BaseMap;
 
 pub const GRID_TILE_SIZE: i32 = 8;
 
@@ -27,6 +29,8 @@ pub struct Map {
     pub visible_tiles: Vec<bool>,
     pub blocked: Vec<bool>,
     pub tile_content: Vec<Vec<Entity>>,
+    /// How strongly the player can still be smelled on each tile, see `ScentSystem`
+    pub scent: Vec<i32>,
 }
 
 impl Map {
@@ -49,6 +53,78 @@ impl Map {
         }
     }
 
+    /// Everything within `radius` of `center` that isn't shielded by walls.
+    /// Uses field of view from the center, so a blast doesn't go through walls.
+    pub fn entities_in_blast(&self, center: rltk::Point, radius: i32) -> Vec<Entity> {
+        let mut blast_tiles = rltk::field_of_view(center, radius, self);
+        blast_tiles
+            .retain(|p| p.x > 0 && p.x < self.width - 1 && p.y > 0 && p.y < self.height - 1);
+
+        let mut victims = Vec::new();
+        for tile in blast_tiles.iter() {
+            let idx = self.xy_idx(tile.x, tile.y);
+            victims.extend(self.tile_content[idx].iter().cloned());
+        }
+
+        victims
+    }
+
+    /// True if nothing opaque or in the way stands on the straight line between `from` and `to`,
+    /// so a shot from one would reach the other instead of hitting whoever is in between
+    pub fn has_clear_shot(&self, from: rltk::Point, to: rltk::Point) -> bool {
+        rltk::line2d(rltk::LineAlg::Bresenham, from, to)
+            .iter()
+            .filter(|p| **p != from && **p != to)
+            .all(|p| {
+                let idx = self.xy_idx(p.x, p.y);
+                !self.is_opaque(idx) && !self.blocked[idx]
+            })
+    }
+
+    /// Walking distance from the nearest of `starts` to every tile. Only walls get in the way,
+    /// whoever is standing around doesn't. Tiles that are unreachable or further than
+    /// `max_depth` stay at f32::MAX.
+    pub fn dijkstra_map(&self, starts: &[usize], max_depth: f32) -> Vec<f32> {
+        const NEIGHBOURS: [(i32, i32); 8] =
+            [(-1, 0), (1, 0), (0, -1), (0, 1), (-1, -1), (1, -1), (-1, 1), (1, 1)];
+
+        let mut distances = vec![f32::MAX; self.tiles.len()];
+        let mut open: VecDeque<usize> = VecDeque::new();
+        for start in starts.iter() {
+            distances[*start] = 0.0;
+            open.push_back(*start);
+        }
+
+        while let Some(idx) = open.pop_front() {
+            let x = idx as i32 % self.width;
+            let y = idx as i32 / self.width;
+            for (dx, dy) in NEIGHBOURS.iter() {
+                let (exit_x, exit_y) = (x + dx, y + dy);
+                if exit_x < 1
+                    || exit_x > self.width - 1
+                    || exit_y < 1
+                    || exit_y > self.height - 1
+                {
+                    continue;
+                }
+                let exit = self.xy_idx(exit_x, exit_y);
+                if self.tiles[exit] == TileType::Wall {
+                    continue;
+                }
+
+                // Same costs as pathfinding
+                let cost = if *dx == 0 || *dy == 0 { 1.0 } else { 1.45 };
+                let distance = distances[idx] + cost;
+                if distance <= max_depth && distance < distances[exit] {
+                    distances[exit] = distance;
+                    open.push_back(exit);
+                }
+            }
+        }
+
+        distances
+    }
+
     pub fn clear_content_index(&mut self) {
         for content in self.tile_content.iter_mut() {
             content.clear();
@@ -94,6 +170,7 @@ impl Map {
             visible_tiles: vec![false; MAP_COUNT],
             blocked: vec![false; MAP_COUNT],
             tile_content: vec![Vec::new(); MAP_COUNT],
+            scent: vec![0; MAP_COUNT],
         };
 
         // @TODO: Remove. Just a test to see if we can actually render an empty map
@@ -1,24 +1,65 @@
 extern crate specs;
-use super::{gamelog::GameLog, CombatStats, Name, SufferDamage, WantsToMelee};
+use super::{
+    gamelog::GameLog, Asleep, Attributes, CombatStats, DamageType, DefenseBonus, Equipped,
+    MeleeDamage, MeleePowerBonus, Name, Noise, Skill, Skills, SufferDamage, WantsToMelee,
+};
+use rltk::RandomNumberGenerator;
 use specs::prelude::*;
 
+/// How far the sound of a fight carries
+pub const COMBAT_NOISE: i32 = 8;
+
+/// Used by anyone without a natural attack or a weapon
+const UNARMED: MeleeDamage = MeleeDamage {
+    n_dice: 1,
+    die_type: 2,
+    kind: DamageType::Bludgeoning,
+};
+
 pub struct MeleeCombatSystem {}
 
 impl<'a> System<'a> for MeleeCombatSystem {
     #[allow(clippy::type_complexity)]
     type SystemData = (
         Entities<'a>,
+        ReadExpect<'a, Entity>,
         WriteExpect<'a, GameLog>,
+        WriteExpect<'a, RandomNumberGenerator>,
         WriteStorage<'a, WantsToMelee>,
         ReadStorage<'a, Name>,
         ReadStorage<'a, CombatStats>,
         WriteStorage<'a, SufferDamage>,
+        ReadStorage<'a, MeleePowerBonus>,
+        ReadStorage<'a, DefenseBonus>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, MeleeDamage>,
+        ReadStorage<'a, Attributes>,
+        WriteStorage<'a, Skills>,
+        WriteStorage<'a, Noise>,
+        ReadStorage<'a, Asleep>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (entities, mut log, mut wants_melee, names, combat_stats, mut inflict_damage) = data;
+        let (
+            entities,
+            player_entity,
+            mut log,
+            mut rng,
+            mut wants_melee,
+            names,
+            combat_stats,
+            mut inflict_damage,
+            melee_power_bonuses,
+            defense_bonuses,
+            equipped,
+            melee_damage,
+            attributes,
+            mut skills,
+            mut noises,
+            asleep,
+        ) = data;
 
-        for (_entity, wants_melee, name, stats) in
+        for (entity, wants_melee, name, stats) in
             (&entities, &wants_melee, &names, &combat_stats).join()
         {
             // If we are NOT dead
@@ -29,26 +70,105 @@ impl<'a> System<'a> for MeleeCombatSystem {
                 if target_stats.hp > 0 {
                     let target_name = names.get(wants_melee.target).unwrap();
 
-                    let damage = i32::max(0, stats.power - target_stats.defense);
+                    // Fighting is loud, hit or miss
+                    noises
+                        .insert(
+                            entity,
+                            Noise {
+                                volume: COMBAT_NOISE,
+                            },
+                        )
+                        .expect("Unable to insert noise");
+
+                    // Add up bonuses from whatever the attacker has equipped
+                    let mut offensive_bonus = 0;
+                    for (power_bonus, equipped_by) in (&melee_power_bonuses, &equipped).join() {
+                        if equipped_by.owner == entity {
+                            offensive_bonus += power_bonus.power;
+                        }
+                    }
+
+                    // A wielded weapon's dice replace the attacker's natural attack
+                    let mut weapon_dice = melee_damage.get(entity).copied().unwrap_or(UNARMED);
+                    for (dice, equipped_by) in (&melee_damage, &equipped).join() {
+                        if equipped_by.owner == entity {
+                            weapon_dice = *dice;
+                        }
+                    }
+
+                    // Attributes and skills are optional, anyone without them is just average
+                    let might_bonus = attributes.get(entity).map_or(0, |a| a.might_bonus());
+                    let melee_skill = skills.get(entity).map_or(0, |s| s.melee.level);
+
+                    let attack = Attack {
+                        attacker: entity,
+                        target: wants_melee.target,
+                        to_hit: melee_skill + might_bonus,
+                        n_dice: weapon_dice.n_dice,
+                        die_type: weapon_dice.die_type,
+                        damage_bonus: stats.power + might_bonus + offensive_bonus,
+                        skill: ("melee", |s| &mut s.melee),
+                    };
+                    let defenses = Defenses {
+                        combat_stats: &combat_stats,
+                        attributes: &attributes,
+                        defense_bonuses: &defense_bonuses,
+                        equipped: &equipped,
+                        asleep: &asleep,
+                    };
+                    let outcome = roll_attack(
+                        &attack,
+                        &defenses,
+                        &mut skills,
+                        &mut rng,
+                        &mut log,
+                        *player_entity,
+                    );
+
+                    let (damage, critical, sneak_attack) = match outcome {
+                        AttackOutcome::Fumble => {
+                            log.entries.push(format!(
+                                "{} fumbles an attack on {}!\n",
+                                &name.name, &target_name.name
+                            ));
+                            continue;
+                        }
+                        AttackOutcome::Miss => {
+                            log.entries
+                                .push(format!("{} misses {}.\n", &name.name, &target_name.name));
+                            continue;
+                        }
+                        AttackOutcome::Hit {
+                            damage,
+                            critical,
+                            sneak_attack,
+                        } => (damage, critical, sneak_attack),
+                    };
 
                     if damage == 0 {
                         // println!("{} is unable to hurt {}", &name.name, &target_name.name);
                         log.entries.push(format!(
-                            "{} is unable to hurt {}\n",
+                            "{} hits {}, but is unable to hurt them.\n",
                             &name.name, &target_name.name
                         ));
                     } else {
-                        // println!(
-                        //     "{} hits {}, for {} hp.",
-                        //     &name.name, &target_name.name, damage
-                        // );
-                        log.entries.push(format!(
-                            "{} hits {}, for {} hp.\n",
-                            &name.name, &target_name.name, damage
-                        ));
-                        inflict_damage
-                            .insert(wants_melee.target, SufferDamage { amount: damage })
-                            .expect("Unable to insert SufferDamage comp");
+                        // How much it actually hurts is logged by the damage system
+                        let verb = if sneak_attack {
+                            "sneak attacks"
+                        } else if critical {
+                            "critically hits"
+                        } else {
+                            "hits"
+                        };
+                        log.entries
+                            .push(format!("{} {} {}.\n", &name.name, verb, &target_name.name));
+                        SufferDamage::new_damage(
+                            &mut inflict_damage,
+                            wants_melee.target,
+                            damage,
+                            weapon_dice.kind,
+                            Some(entity),
+                        );
                     }
                 }
             }
@@ -57,3 +177,147 @@ impl<'a> System<'a> for MeleeCombatSystem {
         wants_melee.clear();
     }
 }
+
+/// How hard `target` is to hit: 10, plus their defense skill, quickness and equipped armor
+pub fn armor_class(
+    target: Entity,
+    skills: &WriteStorage<Skills>,
+    attributes: &ReadStorage<Attributes>,
+    defense_bonuses: &ReadStorage<DefenseBonus>,
+    equipped: &ReadStorage<Equipped>,
+) -> i32 {
+    let mut defensive_bonus = 0;
+    for (defense_bonus, equipped_by) in (defense_bonuses, equipped).join() {
+        if equipped_by.owner == target {
+            defensive_bonus += defense_bonus.defense;
+        }
+    }
+
+    // Attributes and skills are optional, anyone without them is just average
+    let quickness_bonus = attributes.get(target).map_or(0, |a| a.quickness_bonus());
+    let defense_skill = skills.get(target).map_or(0, |s| s.defense.level);
+
+    10 + defense_skill + quickness_bonus + defensive_bonus
+}
+
+/// A single swing or shot at `target`, with everything the attacker brings to it
+pub struct Attack {
+    pub attacker: Entity,
+    pub target: Entity,
+    /// Added to the d20 roll
+    pub to_hit: i32,
+    pub n_dice: i32,
+    pub die_type: i32,
+    /// Added to the damage dice, before the target's defense is taken off
+    pub damage_bonus: i32,
+    /// The attacker's skill that improves when they hit
+    pub skill: (&'static str, fn(&mut Skills) -> &mut Skill),
+}
+
+/// What the target brings to the fight
+pub struct Defenses<'s, 'a> {
+    pub combat_stats: &'s ReadStorage<'a, CombatStats>,
+    pub attributes: &'s ReadStorage<'a, Attributes>,
+    pub defense_bonuses: &'s ReadStorage<'a, DefenseBonus>,
+    pub equipped: &'s ReadStorage<'a, Equipped>,
+    pub asleep: &'s ReadStorage<'a, Asleep>,
+}
+
+pub enum AttackOutcome {
+    /// A natural 1
+    Fumble,
+    Miss,
+    /// `damage` may still be 0 if the target's defense soaks it all up
+    Hit {
+        damage: i32,
+        critical: bool,
+        sneak_attack: bool,
+    },
+}
+
+/// Rolls a d20 plus `to_hit` against the target's armor class, and the damage if it lands.
+/// A natural 1 always misses and a natural 20 always hits, with twice the dice.
+/// Sleeping targets can't dodge, and take double damage.
+/// Hits train the attacker's skill, misses train the target's defense.
+pub fn roll_attack(
+    attack: &Attack,
+    defenses: &Defenses,
+    skills: &mut WriteStorage<Skills>,
+    rng: &mut RandomNumberGenerator,
+    log: &mut GameLog,
+    player_entity: Entity,
+) -> AttackOutcome {
+    let natural_roll = rng.roll_dice(1, 20);
+    let armor_class = armor_class(
+        attack.target,
+        skills,
+        defenses.attributes,
+        defenses.defense_bonuses,
+        defenses.equipped,
+    );
+    let critical = natural_roll == 20;
+    let sneak_attack = defenses.asleep.get(attack.target).is_some();
+
+    if !sneak_attack {
+        if natural_roll == 1 {
+            return AttackOutcome::Fumble;
+        }
+        if !critical && natural_roll + attack.to_hit < armor_class {
+            practice_skill(skills, log, attack.target, player_entity, "defense", |s| {
+                &mut s.defense
+            });
+            return AttackOutcome::Miss;
+        }
+    }
+
+    let (skill_name, pick) = attack.skill;
+    practice_skill(
+        skills,
+        log,
+        attack.attacker,
+        player_entity,
+        skill_name,
+        pick,
+    );
+
+    let mut dice_roll = rng.roll_dice(attack.n_dice, attack.die_type);
+    if critical {
+        dice_roll += rng.roll_dice(attack.n_dice, attack.die_type);
+    }
+    let target_defense = defenses
+        .combat_stats
+        .get(attack.target)
+        .map_or(0, |stats| stats.defense);
+    let mut damage = i32::max(0, dice_roll + attack.damage_bonus - target_defense);
+    if sneak_attack {
+        damage *= 2;
+    }
+
+    AttackOutcome::Hit {
+        damage,
+        critical,
+        sneak_attack,
+    }
+}
+
+/// Counts a use of one of `entity`'s skills, letting the player know when it improves
+pub fn practice_skill<F>(
+    skills: &mut WriteStorage<Skills>,
+    log: &mut GameLog,
+    entity: Entity,
+    player_entity: Entity,
+    skill_name: &str,
+    pick: F,
+) where
+    F: Fn(&mut Skills) -> &mut Skill,
+{
+    if let Some(entity_skills) = skills.get_mut(entity) {
+        let skill = pick(entity_skills);
+        if skill.practice() && entity == player_entity {
+            log.entries.push(format!(
+                "Your {} skill improves to {}.",
+                skill_name, skill.level
+            ));
+        }
+    }
+}
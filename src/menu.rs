@@ -0,0 +1,182 @@
+use ggez::event::KeyCode;
+use ggez::graphics as gfx;
+use ggez::input::mouse;
+use ggez::Context;
+
+use specs::prelude::*;
+
+use super::keybindings::Action;
+use super::map::GRID_TILE_SIZE;
+use super::{Equipped, InBackpack, Name, State};
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum MainMenuSelection {
+    NewGame,
+    Options,
+    Quit,
+}
+
+impl MainMenuSelection {
+    pub fn label(self) -> &'static str {
+        match self {
+            MainMenuSelection::NewGame => "New Game",
+            MainMenuSelection::Options => "Options",
+            MainMenuSelection::Quit => "Quit",
+        }
+    }
+}
+
+/// What happened in the main menu. `Back` returns to the game in progress.
+pub enum MainMenuResult {
+    NoSelection { selected: MainMenuSelection },
+    Selected { selected: MainMenuSelection },
+    Back,
+}
+
+/// Menu entries, top to bottom. There's no saving yet, so no Continue entry either.
+pub const ENTRIES: [MainMenuSelection; 3] = [
+    MainMenuSelection::NewGame,
+    MainMenuSelection::Options,
+    MainMenuSelection::Quit,
+];
+
+/// Screen area of the i-th menu entry, shared by drawing and mouse picking
+pub fn entry_rect(i: usize) -> gfx::Rect {
+    let w = GRID_TILE_SIZE * 20; // @TODO @HARDCODED
+    let h = GRID_TILE_SIZE * 2;
+    let x = (GRID_TILE_SIZE * 80 - w) / 2;
+    let y = GRID_TILE_SIZE * 28 + i as i32 * GRID_TILE_SIZE * 3;
+
+    gfx::Rect::new_i32(x, y, w, h)
+}
+
+/// Keyboard (up/down + enter) and mouse (hover + click) navigation of the main menu.
+/// The menu key goes back to the game, if one is in progress.
+pub fn main_menu_input(
+    gs: &mut State,
+    ctx: &Context,
+    current: MainMenuSelection,
+) -> MainMenuResult {
+    let entries = ENTRIES;
+    let mut idx = entries.iter().position(|e| *e == current).unwrap_or(0);
+
+    // Mouse hovering moves the selection, clicking picks it
+    let mouse_pos = mouse::position(ctx);
+    if let Some(hovered) =
+        (0..entries.len()).find(|i| entry_rect(*i).contains([mouse_pos.x, mouse_pos.y]))
+    {
+        if gs.mouse_clicked {
+            return MainMenuResult::Selected {
+                selected: entries[hovered],
+            };
+        }
+        // Only follow the mouse when it moves, so it doesn't fight the keyboard
+        if gs.mouse_moved {
+            idx = hovered;
+        }
+    }
+
+    if let Some(key) = gs.key {
+        let action = gs.keybindings.action_for(key);
+
+        if key == KeyCode::Up || action == Some(Action::MoveNorth) {
+            idx = if idx == 0 { entries.len() - 1 } else { idx - 1 };
+        } else if key == KeyCode::Down || action == Some(Action::MoveSouth) {
+            idx = (idx + 1) % entries.len();
+        } else if key == KeyCode::Return || key == KeyCode::NumpadEnter {
+            return MainMenuResult::Selected {
+                selected: entries[idx],
+            };
+        } else if action == Some(Action::OpenMenu) && gs.game_in_progress {
+            return MainMenuResult::Back;
+        }
+    }
+
+    MainMenuResult::NoSelection {
+        selected: entries[idx],
+    }
+}
+
+pub enum ItemMenuResult {
+    Cancel,
+    NoResponse,
+    Selected(Entity),
+}
+
+/// Items carried by `owner`, in the order item menus list them
+pub fn backpack_items(ecs: &World, owner: Entity) -> Vec<(Entity, String)> {
+    let entities = ecs.entities();
+    let backpack = ecs.read_storage::<InBackpack>();
+    let names = ecs.read_storage::<Name>();
+
+    (&entities, &backpack, &names)
+        .join()
+        .filter(|(_entity, pack, _name)| pack.owner == owner)
+        .map(|(entity, _pack, name)| (entity, name.name.clone()))
+        .collect()
+}
+
+/// Items `owner` currently has equipped
+pub fn equipped_items(ecs: &World, owner: Entity) -> Vec<(Entity, String)> {
+    let entities = ecs.entities();
+    let equipped = ecs.read_storage::<Equipped>();
+    let names = ecs.read_storage::<Name>();
+
+    (&entities, &equipped, &names)
+        .join()
+        .filter(|(_entity, equipped_by, _name)| equipped_by.owner == owner)
+        .map(|(entity, _equipped_by, name)| (entity, name.name.clone()))
+        .collect()
+}
+
+/// Picking an item from a lettered list: a-z selects, Escape cancels
+pub fn item_menu_input(gs: &State, items: &[(Entity, String)]) -> ItemMenuResult {
+    match gs.key {
+        None => ItemMenuResult::NoResponse,
+        Some(KeyCode::Escape) => ItemMenuResult::Cancel,
+        Some(key) => match letter_to_option(key) {
+            Some(selection) if selection < items.len() => {
+                ItemMenuResult::Selected(items[selection].0)
+            }
+            _ => ItemMenuResult::NoResponse,
+        },
+    }
+}
+
+/// Letter shown next to the i-th entry of an item menu
+pub fn option_letter(i: usize) -> char {
+    (b'a' + i as u8) as char
+}
+
+fn letter_to_option(key: KeyCode) -> Option<usize> {
+    let letters = [
+        KeyCode::A,
+        KeyCode::B,
+        KeyCode::C,
+        KeyCode::D,
+        KeyCode::E,
+        KeyCode::F,
+        KeyCode::G,
+        KeyCode::H,
+        KeyCode::I,
+        KeyCode::J,
+        KeyCode::K,
+        KeyCode::L,
+        KeyCode::M,
+        KeyCode::N,
+        KeyCode::O,
+        KeyCode::P,
+        KeyCode::Q,
+        KeyCode::R,
+        KeyCode::S,
+        KeyCode::T,
+        KeyCode::U,
+        KeyCode::V,
+        KeyCode::W,
+        KeyCode::X,
+        KeyCode::Y,
+        KeyCode::Z,
+    ];
+
+    letters.iter().position(|k| *k == key)
+}
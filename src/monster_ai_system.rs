@@ -1,8 +1,20 @@
 extern crate specs;
-use super::{GridPosition, Map, Monster, RunState, Viewshed, WantsToMelee};
+use super::factions::{FactionTable, Reaction};
+use super::gamelog::GameLog;
+use super::pack_system::Packs;
+use super::{
+    AiState, Asleep, Attributes, Brain, CombatStats, Faction, Follower, GridPosition, Map, Monster,
+    MyTurn, Name, PackMember, RangedAttack, RunState, StatusEffects, Tracker, Viewshed,
+    WantsToMelee, WantsToShoot,
+};
 use specs::prelude::*;
+use std::collections::HashMap;
 
 extern crate rltk;
+use rltk::{BaseMap, Point};
+
+/// How far fleeing monsters look ahead for an escape route
+const FLEE_DEPTH: f32 = 20.0;
 
 pub struct MonsterAISystem {}
 
@@ -11,26 +23,60 @@ impl<'a> System<'a> for MonsterAISystem {
     type SystemData = (
         WriteExpect<'a, Map>,
         ReadExpect<'a, rltk::Point>, // Player pos
-        ReadExpect<'a, Entity>,
+        ReadExpect<'a, FactionTable>,
         ReadExpect<'a, RunState>,
         Entities<'a>,
         WriteStorage<'a, Viewshed>,
         ReadStorage<'a, Monster>,
         WriteStorage<'a, GridPosition>,
         WriteStorage<'a, WantsToMelee>,
+        ReadStorage<'a, StatusEffects>,
+        WriteExpect<'a, rltk::RandomNumberGenerator>,
+        ReadStorage<'a, MyTurn>,
+        WriteStorage<'a, Brain>,
+        ReadStorage<'a, CombatStats>,
+        ReadStorage<'a, Faction>,
+        ReadStorage<'a, Follower>,
+        WriteStorage<'a, Asleep>,
+        ReadStorage<'a, Attributes>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Tracker>,
+        ReadStorage<'a, RangedAttack>,
+        WriteStorage<'a, WantsToShoot>,
+        ReadExpect<'a, Packs>,
+        ReadStorage<'a, PackMember>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
         let (
             mut map,
             player_pos,
-            player_entity,
+            faction_table,
             runstate,
             entities,
             mut viewshed,
             monster,
             mut position,
             mut wants_to_melee,
+            status_effects,
+            mut rng,
+            turns,
+            mut brains,
+            combat_stats,
+            factions,
+            followers,
+            mut asleep,
+            attributes,
+            player_entity,
+            mut log,
+            names,
+            trackers,
+            ranged_attacks,
+            mut wants_to_shoot,
+            packs,
+            pack_members,
         ) = data;
 
         // Early exit if this is not Monster's turn
@@ -38,41 +84,320 @@ impl<'a> System<'a> for MonsterAISystem {
             return;
         }
 
-        for (entity, mut viewshed, _monster, mut pos) in
-            (&entities, &mut viewshed, &monster, &mut position).join()
+        // Only worked out for threats someone actually runs from, one map per threat
+        let mut flee_maps: HashMap<usize, Vec<f32>> = HashMap::new();
+
+        // Only monsters with enough energy get to act
+        for (entity, viewshed, _monster, pos, _turn, brain) in (
+            &entities,
+            &mut viewshed,
+            &monster,
+            &mut position,
+            &turns,
+            &mut brains,
+        )
+            .join()
         {
-            let distance = rltk::DistanceAlg::Pythagoras
-                .distance2d(rltk::Point::new(pos.x, pos.y), *player_pos);
-            if distance < 1.5 {
-                // Attack goes here
-                wants_to_melee
-                    .insert(
-                        entity,
-                        WantsToMelee {
-                            target: *player_entity,
-                        },
-                    )
-                    .expect("Unable to insert attack");
-            } else if viewshed.visible_tiles.contains(&*player_pos) {
-                // Get a path to player so we can follow him and attack when close
-
-                let path = rltk::a_star_search(
-                    map.xy_idx(pos.x, pos.y) as i32,
-                    map.xy_idx(player_pos.x, player_pos.y) as i32,
-                    &mut *map,
+            if let Some(effects) = status_effects.get(entity) {
+                // Stunned monsters lose their turn
+                if effects.is_stunned() {
+                    continue;
+                }
+
+                // Confused monsters stumble around at random
+                if effects.is_confused() {
+                    random_step(&mut map, pos, viewshed, &mut rng);
+                    continue;
+                }
+            }
+
+            // Sleeping monsters only notice the player if they fail to sneak past,
+            // which they get one go at each time they come into view
+            if let Some(sleep) = asleep.get_mut(entity) {
+                let sees_player = viewshed.visible_tiles.contains(&*player_pos);
+                let just_spotted = sees_player && !sleep.saw_player;
+                sleep.saw_player = sees_player;
+
+                if just_spotted {
+                    let stealth = rng.roll_dice(1, 20)
+                        + attributes
+                            .get(*player_entity)
+                            .map_or(0, |a| a.quickness_bonus());
+                    let perception =
+                        10 + attributes.get(entity).map_or(0, |a| a.intelligence_bonus());
+
+                    if stealth < perception {
+                        asleep.remove(entity);
+                        if let Some(name) = names.get(entity) {
+                            log.entries.push(format!("{} wakes up!\n", name.name));
+                        }
+                    }
+                }
+                continue;
+            }
+
+            let my_pos = Point::new(pos.x, pos.y);
+            let my_faction = factions.get(entity).map_or("", |f| f.name.as_str());
+
+            // Closest enemy to go for, and closest thing to run away from
+            let mut enemy: Option<(Entity, Point, f32)> = None;
+            let mut threat: Option<(Point, f32)> = None;
+            for tile in viewshed.visible_tiles.iter() {
+                let idx = map.xy_idx(tile.x, tile.y);
+                for other in map.tile_content[idx].iter() {
+                    let their_faction = match factions.get(*other) {
+                        Some(faction) if *other != entity => faction,
+                        _ => continue,
+                    };
+                    if combat_stats.get(*other).is_none_or(|s| s.hp < 1) {
+                        continue;
+                    }
+
+                    let distance = rltk::DistanceAlg::Pythagoras.distance2d(my_pos, *tile);
+                    match faction_table.reaction(my_faction, &their_faction.name) {
+                        Reaction::Attack => {
+                            if enemy.is_none_or(|(_, _, closest)| distance < closest) {
+                                enemy = Some((*other, *tile, distance));
+                            }
+                        }
+                        Reaction::Flee => {
+                            if threat.is_none_or(|(_, closest)| distance < closest) {
+                                threat = Some((*tile, distance));
+                            }
+                        }
+                        Reaction::Ignore => {}
+                    }
+                }
+            }
+
+            let badly_hurt = combat_stats
+                .get(entity)
+                .is_some_and(|s| s.hp * 100 < s.max_hp * brain.flee_below);
+            let routed = pack_members.get(entity).is_some_and(|m| m.routed);
+
+            // Work out what to do from what we can see
+            let was_following = brain.state == AiState::Follow;
+            brain.state = match (enemy, threat) {
+                (_, Some((from, _))) => AiState::Flee { from },
+                (Some((_, from, _)), None) if badly_hurt || routed => AiState::Flee { from },
+                (Some((target, target_pos, _)), None) => {
+                    brain.last_seen = Some(target_pos);
+                    AiState::Chase { target }
+                }
+                (None, None) => match brain.state {
+                    AiState::Chase { .. } => AiState::Search {
+                        target: brain.last_seen.unwrap_or(my_pos),
+                    },
+                    AiState::Search { target } if target == my_pos => AiState::Wander,
+                    AiState::Flee { .. } => AiState::Wander,
+                    state => state,
+                },
+            };
+
+            // Trackers pick up the player's trail once they've lost sight of everyone
+            if trackers.get(entity).is_some()
+                && matches!(
+                    brain.state,
+                    AiState::Wander | AiState::Search { .. } | AiState::Track
+                )
+            {
+                let my_idx = map.xy_idx(pos.x, pos.y);
+                if scent_step(&map, my_idx).is_some() {
+                    brain.state = AiState::Track;
+                } else if brain.state == AiState::Track {
+                    // Trail's gone cold
+                    brain.state = AiState::Wander;
+                }
+            }
+
+            // Packs go and have a look wherever one of them has spotted an enemy
+            let pack = pack_members.get(entity).and_then(|m| packs.get(m.pack));
+            if let Some(sighting) = pack.and_then(|p| p.sighting) {
+                let idle = matches!(
+                    brain.state,
+                    AiState::Wander | AiState::Search { .. } | AiState::Track
                 );
+                if idle && sighting != my_pos {
+                    brain.state = AiState::Search { target: sighting };
+                }
+            }
+
+            // Allies come back to the player when idle or when they've strayed too far.
+            // Once heading back they keep going until well inside the leash, so they
+            // don't flip between fighting and following right at its edge.
+            if let Some(follower) = followers.get(entity) {
+                let to_player = rltk::DistanceAlg::Pythagoras.distance2d(my_pos, *player_pos);
+                let idle = matches!(brain.state, AiState::Wander | AiState::Search { .. });
+                let too_far = to_player > follower.leash as f32
+                    || (was_following && to_player >= (follower.leash - 2) as f32);
+                if idle || too_far {
+                    brain.state = AiState::Follow;
+                }
+            }
+
+            match brain.state {
+                AiState::Wander => random_step(&mut map, pos, viewshed, &mut rng),
+                AiState::Chase { target } => {
+                    let target_pos = brain.last_seen.unwrap_or(my_pos);
+                    let distance = rltk::DistanceAlg::Pythagoras.distance2d(my_pos, target_pos);
+                    let ranged = ranged_attacks.get(entity);
+
+                    // Ranged attackers keep their distance, if there's somewhere to back off to
+                    let my_idx = map.xy_idx(pos.x, pos.y);
+                    let back_off = ranged
+                        .filter(|attack| distance < attack.keep_away as f32)
+                        .and_then(|_| {
+                            let target_idx = map.xy_idx(target_pos.x, target_pos.y);
+                            let distances = flee_maps
+                                .entry(target_idx)
+                                .or_insert_with(|| map.dijkstra_map(&[target_idx], FLEE_DEPTH));
+                            flee_step(&map, distances, my_idx)
+                        });
+                    let clear_shot = ranged.is_some_and(|attack| {
+                        distance <= attack.range as f32 && map.has_clear_shot(my_pos, target_pos)
+                    });
 
-                if path.success && path.steps.len() > 1 {
-                    let mut idx = map.xy_idx(pos.x, pos.y);
-                    map.blocked[idx] = false; // Release our current tile from blocked
-                    pos.x = path.steps[1] as i32 % map.width;
-                    pos.y = path.steps[1] as i32 / map.width;
-                    // Pathing  already takes care that we don't move on a blocked tile
-                    idx = map.xy_idx(pos.x, pos.y);
-                    map.blocked[idx] = true;
-                    viewshed.dirty = true;
+                    if let Some(step) = back_off {
+                        move_to(&mut map, pos, viewshed, step);
+                    } else if distance < 1.5 {
+                        // Attack goes here
+                        wants_to_melee
+                            .insert(entity, WantsToMelee { target })
+                            .expect("Unable to insert attack");
+                    } else if clear_shot {
+                        wants_to_shoot
+                            .insert(entity, WantsToShoot { target })
+                            .expect("Unable to insert shot");
+                    } else {
+                        // Packs spread out to surround the target rather than queue up behind
+                        // each other, heading for the spot next to it the pack handed out.
+                        // The target may have moved since, so only if it's still next to them.
+                        let flank = pack
+                            .and_then(|p| p.flank_spots.get(&entity))
+                            .filter(|spot| {
+                                rltk::DistanceAlg::Pythagoras.distance2d(**spot, target_pos) < 1.5
+                            })
+                            .and_then(|spot| path_step(&mut map, pos, *spot));
+
+                        // Otherwise get a path to the target so we can follow them and attack
+                        // when close
+                        if let Some(step) = flank.or_else(|| path_step(&mut map, pos, target_pos)) {
+                            move_to(&mut map, pos, viewshed, step);
+                        }
+                    }
+                }
+                AiState::Search { target } => match path_step(&mut map, pos, target) {
+                    Some(step) => move_to(&mut map, pos, viewshed, step),
+                    // Can't get there, so give up
+                    None => brain.state = AiState::Wander,
+                },
+                AiState::Follow => {
+                    // Close enough is good enough, no need to crowd the player
+                    let to_player = rltk::DistanceAlg::Pythagoras.distance2d(my_pos, *player_pos);
+                    if to_player > 2.0 {
+                        if let Some(step) = path_step(&mut map, pos, *player_pos) {
+                            move_to(&mut map, pos, viewshed, step);
+                        }
+                    }
+                }
+                AiState::Track => {
+                    let my_idx = map.xy_idx(pos.x, pos.y);
+                    if let Some(step) = scent_step(&map, my_idx) {
+                        move_to(&mut map, pos, viewshed, step);
+                    }
+                }
+                AiState::Flee { from } => {
+                    let from_idx = map.xy_idx(from.x, from.y);
+                    let distances = flee_maps
+                        .entry(from_idx)
+                        .or_insert_with(|| map.dijkstra_map(&[from_idx], FLEE_DEPTH));
+
+                    // Head for whichever neighbouring tile is furthest from the threat
+                    let my_idx = map.xy_idx(pos.x, pos.y);
+                    match flee_step(&map, distances, my_idx) {
+                        Some(step) => move_to(&mut map, pos, viewshed, step),
+                        None => {
+                            // Cornered, so fight back if it's something we'd fight
+                            if let Some((target, target_pos, _)) = enemy {
+                                let distance =
+                                    rltk::DistanceAlg::Pythagoras.distance2d(my_pos, target_pos);
+                                if distance < 1.5 {
+                                    wants_to_melee
+                                        .insert(entity, WantsToMelee { target })
+                                        .expect("Unable to insert attack");
+                                }
+                            }
+                        }
+                    }
                 }
             }
         }
     }
 }
+
+/// Moves onto `new_idx`, keeping the map's blocked tiles up to date
+fn move_to(map: &mut Map, pos: &mut GridPosition, viewshed: &mut Viewshed, new_idx: usize) {
+    let idx = map.xy_idx(pos.x, pos.y);
+    map.blocked[idx] = false; // Release our current tile from blocked
+    pos.x = new_idx as i32 % map.width;
+    pos.y = new_idx as i32 / map.width;
+    map.blocked[new_idx] = true;
+    viewshed.dirty = true;
+}
+
+/// Steps in a random direction, if that tile is free
+fn random_step(
+    map: &mut Map,
+    pos: &mut GridPosition,
+    viewshed: &mut Viewshed,
+    rng: &mut rltk::RandomNumberGenerator,
+) {
+    let new_x = pos.x + rng.range(-1, 2);
+    let new_y = pos.y + rng.range(-1, 2);
+    let new_idx = map.xy_idx(new_x, new_y);
+    if !map.blocked[new_idx] {
+        move_to(map, pos, viewshed, new_idx);
+    }
+}
+
+/// The free neighbouring tile with the strongest scent, if it smells stronger than where we are
+fn scent_step(map: &Map, idx: usize) -> Option<usize> {
+    map.get_available_exits(idx)
+        .iter()
+        .map(|(exit, _cost)| *exit)
+        .filter(|exit| map.scent[*exit] > map.scent[idx])
+        .max_by_key(|exit| map.scent[*exit])
+}
+
+/// Whichever free neighbouring tile is furthest from whatever `distances` were measured from,
+/// as long as it actually gets us further away
+fn flee_step(map: &Map, distances: &[f32], idx: usize) -> Option<usize> {
+    map.get_available_exits(idx)
+        .iter()
+        .map(|(exit, _cost)| *exit)
+        .filter(|exit| distances[*exit] > distances[idx])
+        .max_by(|a, b| distances[*a].partial_cmp(&distances[*b]).unwrap())
+}
+
+/// The first step on the way from `pos` to `target`, if there's a way there
+fn path_step(map: &mut Map, pos: &GridPosition, target: Point) -> Option<usize> {
+    // Whoever is standing on the target shouldn't stop us finding a way to them
+    let target_idx = map.xy_idx(target.x, target.y);
+    let target_blocked = map.blocked[target_idx];
+    map.blocked[target_idx] = false;
+
+    let path = rltk::a_star_search(map.xy_idx(pos.x, pos.y) as i32, target_idx as i32, &*map);
+    map.blocked[target_idx] = target_blocked;
+
+    // Pathing  already takes care that we don't move on a blocked tile,
+    // except for the target itself if someone is standing there
+    if path.success && path.steps.len() > 1 {
+        let step = path.steps[1] as usize;
+        if step == target_idx && target_blocked {
+            return None;
+        }
+        Some(step)
+    } else {
+        None
+    }
+}
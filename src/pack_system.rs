@@ -0,0 +1,141 @@
+extern crate specs;
+use super::{gamelog::GameLog, AiState, Brain, CombatStats, GridPosition, Map, PackMember};
+use rltk::{BaseMap, Point, RandomNumberGenerator};
+use specs::prelude::*;
+use std::collections::HashMap;
+
+/// Members roll a d20 when their leader dies, and run for it on anything below this
+const MORALE: i32 = 12;
+
+/// What a group of monsters knows together
+pub struct Pack {
+    /// None once the leader is dead
+    pub leader: Option<Entity>,
+    /// Kept around for the log, as the leader's `Name` goes when they die
+    pub leader_name: String,
+    /// Where one of the pack last saw an enemy it's chasing
+    pub sighting: Option<Point>,
+    /// The free spot next to their target each chasing member should head for
+    pub flank_spots: HashMap<Entity, Point>,
+}
+
+/// Every pack on the map, indexed by `PackMember::pack`
+#[derive(Default)]
+pub struct Packs {
+    packs: Vec<Pack>,
+}
+
+impl Packs {
+    /// Starts a new pack led by `leader`, returning its id
+    pub fn new_pack(&mut self, leader: Entity, leader_name: String) -> usize {
+        self.packs.push(Pack {
+            leader: Some(leader),
+            leader_name,
+            sighting: None,
+            flank_spots: HashMap::new(),
+        });
+        self.packs.len() - 1
+    }
+
+    pub fn get(&self, pack: usize) -> Option<&Pack> {
+        self.packs.get(pack)
+    }
+}
+
+/// Keeps every pack up to date: shares sightings, spreads chasers out around their target
+/// and breaks their morale when the leader dies
+pub struct PackSystem {}
+
+impl<'a> System<'a> for PackSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        WriteExpect<'a, Packs>,
+        ReadExpect<'a, Map>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        Entities<'a>,
+        WriteStorage<'a, PackMember>,
+        ReadStorage<'a, Brain>,
+        ReadStorage<'a, CombatStats>,
+        ReadStorage<'a, GridPosition>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            mut packs,
+            map,
+            mut log,
+            mut rng,
+            entities,
+            mut members,
+            brains,
+            combat_stats,
+            positions,
+        ) = data;
+
+        let mut leaderless: Vec<usize> = Vec::new();
+        for (id, pack) in packs.packs.iter_mut().enumerate() {
+            // Sightings and spots are only good for as long as someone is still chasing
+            pack.sighting = None;
+            pack.flank_spots.clear();
+
+            if let Some(leader) = pack.leader {
+                let dead =
+                    !entities.is_alive(leader) || combat_stats.get(leader).is_none_or(|s| s.hp < 1);
+                if dead {
+                    pack.leader = None;
+                    leaderless.push(id);
+                    log.entries.push(format!(
+                        "With {} dead, the rest of the pack loses heart!",
+                        pack.leader_name
+                    ));
+                }
+            }
+        }
+
+        // Who in each pack is chasing what: (pack, member, where they are, where the target is)
+        let mut chasers: Vec<(usize, Entity, Point, Point)> = Vec::new();
+        for (entity, member, brain, pos) in (&entities, &mut members, &brains, &positions).join() {
+            // Anyone who fails their morale roll runs as soon as they see an enemy
+            if leaderless.contains(&member.pack) && rng.roll_dice(1, 20) < MORALE {
+                member.routed = true;
+            }
+
+            if let AiState::Chase { .. } = brain.state {
+                packs.packs[member.pack].sighting = brain.last_seen;
+                if let Some(target) = brain.last_seen {
+                    chasers.push((member.pack, entity, Point::new(pos.x, pos.y), target));
+                }
+            }
+        }
+
+        assign_flank_spots(&mut packs, &map, &chasers);
+    }
+}
+
+/// Hands out the free spots next to each target, closest member and spot first, so a pack
+/// surrounds its target instead of queueing up behind each other. No spot goes out twice.
+fn assign_flank_spots(packs: &mut Packs, map: &Map, chasers: &[(usize, Entity, Point, Point)]) {
+    let mut options: Vec<(f32, usize, Entity, Point)> = Vec::new();
+    for (pack, entity, pos, target) in chasers.iter() {
+        // Already close enough to fight
+        if rltk::DistanceAlg::Pythagoras.distance2d(*pos, *target) < 1.5 {
+            continue;
+        }
+
+        let target_idx = map.xy_idx(target.x, target.y);
+        for (exit, _cost) in map.get_available_exits(target_idx).iter() {
+            let spot = Point::new(*exit as i32 % map.width, *exit as i32 / map.width);
+            let distance = rltk::DistanceAlg::Pythagoras.distance2d(*pos, spot);
+            options.push((distance, *pack, *entity, spot));
+        }
+    }
+    options.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+    for (_distance, pack, entity, spot) in options {
+        let flank_spots = &mut packs.packs[pack].flank_spots;
+        if !flank_spots.contains_key(&entity) && !flank_spots.values().any(|s| *s == spot) {
+            flank_spots.insert(entity, spot);
+        }
+    }
+}
@@ -1,16 +1,32 @@
-use ggez::event::KeyCode;
-use ggez::input::keyboard;
-use ggez::Context;
-
 use specs::prelude::*;
 
-use super::{CombatStats, GridPosition, Map, Player, RunState, State, Viewshed, WantsToMelee};
+use super::gamelog::GameLog;
+use super::keybindings::Action;
+use super::menu::MainMenuSelection;
+use super::{
+    CombatStats, Follower, GridPosition, HungerClock, HungerState, Item, Map, Monster, Player,
+    Resting, RunState, State, StatusEffects, Viewshed, WantsToMelee, WantsToPickupItem,
+};
 
 use std::cmp::{max, min};
 
 // @TODO: Not refactoring for RLT 1.3. Just moved stuff here!!
 
 pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
+    // Confused players stumble in a random direction instead
+    let (delta_x, delta_y) = if player_is_confused(ecs) {
+        let mut rng = ecs.write_resource::<rltk::RandomNumberGenerator>();
+        let mut log = ecs.fetch_mut::<GameLog>();
+        log.entries
+            .push("You stumble around in confusion.".to_string());
+        (rng.range(-1, 2), rng.range(-1, 2))
+    } else {
+        (delta_x, delta_y)
+    };
+    if delta_x == 0 && delta_y == 0 {
+        return;
+    }
+
     let mut positions = ecs.write_storage::<GridPosition>();
     let mut players = ecs.write_storage::<Player>();
     let mut viewsheds = ecs.write_storage::<Viewshed>();
@@ -18,6 +34,9 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
     let combat_stats = ecs.read_storage::<CombatStats>();
     let map = ecs.fetch::<Map>();
     let mut wants_to_melee = ecs.write_storage::<WantsToMelee>();
+    let followers = ecs.read_storage::<Follower>();
+    // An ally we traded places with, and where they end up
+    let mut swapped: Option<(Entity, i32, i32)> = None;
 
     for (entity, _player, pos, viewshed) in
         (&entities, &mut players, &mut positions, &mut viewsheds).join()
@@ -33,6 +52,23 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
 
         let destination_idx = map.xy_idx(pos.x + delta_x, pos.y + delta_y);
 
+        // Bumping into an ally trades places with them instead of attacking
+        let ally = map.tile_content[destination_idx]
+            .iter()
+            .find(|e| followers.get(**e).is_some())
+            .copied();
+        if let Some(ally) = ally {
+            swapped = Some((ally, pos.x, pos.y));
+            pos.x += delta_x;
+            pos.y += delta_y;
+            viewshed.dirty = true;
+
+            let mut ppos = ecs.write_resource::<rltk::Point>();
+            ppos.x = pos.x;
+            ppos.y = pos.y;
+            continue;
+        }
+
         // Let's see if we are moving onto an enemy. If so attack and return without moving
         // We check the dest tiles contained entities for that.
         for potential_target in map.tile_content[destination_idx].iter() {
@@ -65,84 +101,265 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
             ppos.y = pos.y;
         }
     }
+
+    if let Some((ally, x, y)) = swapped {
+        if let Some(ally_pos) = positions.get_mut(ally) {
+            ally_pos.x = x;
+            ally_pos.y = y;
+        }
+        if let Some(ally_viewshed) = viewsheds.get_mut(ally) {
+            ally_viewshed.dirty = true;
+        }
+    }
 }
 
-pub fn player_input(gs: &mut State, ctx: &Context) -> RunState {
-    // Player movement
-    // @TODO: Return RunState::Waiting when none active key is pressed!!!!!!
-    let pressed_keys = keyboard::pressed_keys(ctx);
-    if pressed_keys.is_empty() {
-        return RunState::AwaitingInput;
-    } else {
-        if keyboard::is_key_pressed(ctx, KeyCode::Left)
-            || keyboard::is_key_pressed(ctx, KeyCode::Numpad4)
-            || keyboard::is_key_pressed(ctx, KeyCode::H)
-        {
-            try_move_player(-1, 0, &mut gs.ecs);
-            // @TODO: Fix!!!!
-            return RunState::PlayerTurn;
+fn player_is_confused(ecs: &World) -> bool {
+    let player_entity = ecs.fetch::<Entity>();
+    let status_effects = ecs.read_storage::<StatusEffects>();
+
+    match status_effects.get(*player_entity) {
+        Some(effects) => effects.is_confused(),
+        None => false,
+    }
+}
+
+/// Called while awaiting input. A stunned player loses their turn.
+pub fn skip_turn_if_stunned(gs: &mut State) -> Option<RunState> {
+    let stunned = {
+        let player_entity = gs.ecs.fetch::<Entity>();
+        let status_effects = gs.ecs.read_storage::<StatusEffects>();
+        match status_effects.get(*player_entity) {
+            Some(effects) => effects.is_stunned(),
+            None => false,
         }
+    };
 
-        if keyboard::is_key_pressed(ctx, KeyCode::Right)
-            || keyboard::is_key_pressed(ctx, KeyCode::Numpad6)
-            || keyboard::is_key_pressed(ctx, KeyCode::L)
-        {
-            try_move_player(1, 0, &mut gs.ecs);
-            // @TODO: Fix!!!!
-            return RunState::PlayerTurn;
+    if !stunned {
+        return None;
+    }
+
+    gs.ecs
+        .fetch_mut::<GameLog>()
+        .entries
+        .push("You are stunned and can't act!".to_string());
+    Some(RunState::PlayerTurn)
+}
+
+/// True if the player can currently see any monster that isn't on their side
+pub fn enemy_in_view(ecs: &World) -> bool {
+    let viewsheds = ecs.read_storage::<Viewshed>();
+    let monsters = ecs.read_storage::<Monster>();
+    let followers = ecs.read_storage::<Follower>();
+    let player_entity = ecs.fetch::<Entity>();
+    let map = ecs.fetch::<Map>();
+
+    if let Some(viewshed) = viewsheds.get(*player_entity) {
+        for tile in viewshed.visible_tiles.iter() {
+            let idx = map.xy_idx(tile.x, tile.y);
+            for entity in map.tile_content[idx].iter() {
+                if monsters.get(*entity).is_some() && followers.get(*entity).is_none() {
+                    return true;
+                }
+            }
         }
+    }
 
-        if keyboard::is_key_pressed(ctx, KeyCode::Up)
-            || keyboard::is_key_pressed(ctx, KeyCode::Numpad8)
-            || keyboard::is_key_pressed(ctx, KeyCode::K)
-        {
-            try_move_player(0, -1, &mut gs.ecs);
-            // @TODO: Fix!!!!
-            return RunState::PlayerTurn;
+    false
+}
+
+/// Picks up the first item lying under the player, using the map's tile content index
+fn get_item(ecs: &mut World) -> RunState {
+    let player_pos = *ecs.fetch::<rltk::Point>();
+    let player_entity = *ecs.fetch::<Entity>();
+    let items = ecs.read_storage::<Item>();
+    let map = ecs.fetch::<Map>();
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
+
+    let idx = map.xy_idx(player_pos.x, player_pos.y);
+    let target_item = map.tile_content[idx]
+        .iter()
+        .find(|entity| items.get(**entity).is_some());
+
+    match target_item {
+        None => {
+            gamelog
+                .entries
+                .push("There is nothing here to pick up.".to_string());
+            RunState::AwaitingInput
+        }
+        Some(item) => {
+            let mut pickup = ecs.write_storage::<WantsToPickupItem>();
+            pickup
+                .insert(
+                    player_entity,
+                    WantsToPickupItem {
+                        collected_by: player_entity,
+                        item: *item,
+                    },
+                )
+                .expect("Unable to insert want to pickup");
+            RunState::PlayerTurn
         }
+    }
+}
 
-        if keyboard::is_key_pressed(ctx, KeyCode::Down)
-            || keyboard::is_key_pressed(ctx, KeyCode::Numpad2)
-            || keyboard::is_key_pressed(ctx, KeyCode::J)
-        {
-            try_move_player(0, 1, &mut gs.ecs);
-            // @TODO: Fix!!!!
-            return RunState::PlayerTurn;
+/// Why the player can't rest, if something keeps hurting them
+fn wearing_down(ecs: &World) -> Option<&'static str> {
+    let player_entity = *ecs.fetch::<Entity>();
+    let starving = ecs
+        .read_storage::<HungerClock>()
+        .get(player_entity)
+        .is_some_and(|clock| clock.state == HungerState::Starving);
+    let poisoned = ecs
+        .read_storage::<StatusEffects>()
+        .get(player_entity)
+        .is_some_and(|effects| effects.is_poisoned());
+
+    if starving {
+        Some("You are too hungry to rest.")
+    } else if poisoned {
+        Some("You can't rest while poisoned.")
+    } else {
+        None
+    }
+}
+
+fn player_hp(ecs: &World) -> i32 {
+    let player_entity = *ecs.fetch::<Entity>();
+    ecs.read_storage::<CombatStats>()
+        .get(player_entity)
+        .map_or(0, |stats| stats.hp)
+}
+
+fn start_resting(ecs: &mut World) -> RunState {
+    let refusal = if enemy_in_view(ecs) {
+        Some("You can't rest with enemies nearby.")
+    } else {
+        wearing_down(ecs)
+    };
+    if let Some(message) = refusal {
+        let mut log = ecs.fetch_mut::<GameLog>();
+        log.entries.push(message.to_string());
+        return RunState::AwaitingInput;
+    }
+
+    let player_entity = *ecs.fetch::<Entity>();
+    let last_hp = player_hp(ecs);
+    ecs.write_storage::<Resting>()
+        .insert(player_entity, Resting { last_hp })
+        .expect("Unable to insert Resting");
+
+    let mut log = ecs.fetch_mut::<GameLog>();
+    log.entries.push("You settle down to rest.".to_string());
+
+    RunState::PlayerTurn
+}
+
+/// Called while awaiting input. If the player is resting this passes another turn,
+/// unless they are fully healed, a monster shows up, they got hurt, something keeps
+/// hurting them or a key was pressed.
+pub fn continue_resting(gs: &mut State) -> Option<RunState> {
+    let player_entity = *gs.ecs.fetch::<Entity>();
+    let hp = player_hp(&gs.ecs);
+    let hurt = {
+        let mut resting = gs.ecs.write_storage::<Resting>();
+        // Nothing to carry on with unless resting
+        let resting = resting.get_mut(player_entity)?;
+        let hurt = hp < resting.last_hp;
+        resting.last_hp = hp;
+        hurt
+    };
+
+    let healed = {
+        let combat_stats = gs.ecs.read_storage::<CombatStats>();
+        match combat_stats.get(player_entity) {
+            Some(stats) => stats.hp >= stats.max_hp,
+            None => true,
         }
+    };
 
-        // DIAGONALS
-        if keyboard::is_key_pressed(ctx, KeyCode::Numpad9)
-            || keyboard::is_key_pressed(ctx, KeyCode::Y)
-        {
-            try_move_player(1, -1, &mut gs.ecs);
-            // @TODO: Fix!!!!
-            return RunState::PlayerTurn;
+    let interruption = if healed {
+        Some("You feel rested.")
+    } else if enemy_in_view(&gs.ecs) {
+        Some("A monster comes into view! You stop resting.")
+    } else if hurt {
+        Some("You are getting hurt! You stop resting.")
+    } else if let Some(message) = wearing_down(&gs.ecs) {
+        Some(message)
+    } else if gs.key.is_some() {
+        Some("You stop resting.")
+    } else {
+        None
+    };
+
+    match interruption {
+        None => Some(RunState::PlayerTurn),
+        Some(message) => {
+            gs.ecs.write_storage::<Resting>().remove(player_entity);
+            gs.ecs
+                .fetch_mut::<GameLog>()
+                .entries
+                .push(message.to_string());
+            // Swallow the key that interrupted us
+            gs.key = None;
+            Some(RunState::AwaitingInput)
         }
+    }
+}
 
-        if keyboard::is_key_pressed(ctx, KeyCode::Numpad7)
-            || keyboard::is_key_pressed(ctx, KeyCode::U)
-        {
-            try_move_player(-1, -1, &mut gs.ecs);
-            // @TODO: Fix!!!!
-            return RunState::PlayerTurn;
+pub fn player_input(gs: &mut State) -> RunState {
+    // Player movement
+    let key = match gs.key {
+        None => return RunState::AwaitingInput, // Nothing happened
+        Some(key) => key,
+    };
+
+    match gs.keybindings.action_for(key) {
+        Some(Action::MoveWest) => try_move_player(-1, 0, &mut gs.ecs),
+        Some(Action::MoveEast) => try_move_player(1, 0, &mut gs.ecs),
+        Some(Action::MoveNorth) => try_move_player(0, -1, &mut gs.ecs),
+        Some(Action::MoveSouth) => try_move_player(0, 1, &mut gs.ecs),
+
+        // DIAGONALS
+        Some(Action::MoveNorthEast) => try_move_player(1, -1, &mut gs.ecs),
+        Some(Action::MoveNorthWest) => try_move_player(-1, -1, &mut gs.ecs),
+        Some(Action::MoveSouthEast) => try_move_player(1, 1, &mut gs.ecs),
+        Some(Action::MoveSouthWest) => try_move_player(-1, 1, &mut gs.ecs),
+
+        // Skip a turn
+        Some(Action::Wait) => {}
+        Some(Action::Rest) => return start_resting(&mut gs.ecs),
+
+        // Items
+        Some(Action::PickUp) => return get_item(&mut gs.ecs),
+        Some(Action::ShowInventory) => return RunState::ShowInventory,
+        Some(Action::DropItem) => return RunState::ShowDropItem,
+        Some(Action::RemoveItem) => return RunState::ShowRemoveItem,
+
+        // There are no stairs yet, so this doesn't take a turn
+        Some(Action::Descend) => {
+            gs.ecs
+                .fetch_mut::<GameLog>()
+                .entries
+                .push("There is no way down here.".to_string());
+            return RunState::AwaitingInput;
         }
 
-        if keyboard::is_key_pressed(ctx, KeyCode::Numpad3)
-            || keyboard::is_key_pressed(ctx, KeyCode::N)
-        {
-            try_move_player(1, 1, &mut gs.ecs);
-            // @TODO: Fix!!!!
-            return RunState::PlayerTurn;
+        Some(Action::OpenMenu) => {
+            return RunState::MainMenu {
+                menu_selection: MainMenuSelection::NewGame,
+            }
         }
 
-        if keyboard::is_key_pressed(ctx, KeyCode::Numpad1)
-            || keyboard::is_key_pressed(ctx, KeyCode::B)
-        {
-            try_move_player(-1, 1, &mut gs.ecs);
-            // @TODO: Fix!!!!
-            return RunState::PlayerTurn;
+        // Doesn't take a turn
+        Some(Action::ToggleDebug) => {
+            gs.show_debug = !gs.show_debug;
+            return RunState::AwaitingInput;
         }
+
+        // If a key is pressed BUT is not one of the controlling keys then don't change state
+        _ => return RunState::AwaitingInput,
     }
-    // If a key is pressed BUT is not one of the controlling keys then don't change state
-    RunState::AwaitingInput
+
+    RunState::PlayerTurn
 }
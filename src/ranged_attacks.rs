@@ -0,0 +1,180 @@
+use ggez::{Context, GameResult};
+
+use std::collections::{BTreeMap, HashMap};
+
+use super::config_file;
+use super::spawner::MONSTER_TYPES;
+use super::{DamageType, RangedAttack};
+
+/// Path (inside ggez's resource dirs) of the ranged attacks file
+pub const RANGED_ATTACKS_FILE: &str = "/ranged_attacks.cfg";
+
+/// A monster type armed with one of the attacks, spawned under its own name
+#[derive(Clone)]
+pub struct ArmedMonster {
+    /// One of the spawner's `MONSTER_TYPES`
+    pub monster: String,
+    pub name: String,
+    pub attack: RangedAttack,
+}
+
+/// Every ranged attack in the file, by name, along with who carries it
+#[derive(Clone)]
+pub struct RangedAttackTable {
+    // Sorted, so picking one at random is the same for the same seed
+    attacks: BTreeMap<String, ArmedMonster>,
+}
+
+impl RangedAttackTable {
+    /// Loads the ranged attacks file from the ggez filesystem
+    pub fn load(ctx: &mut Context) -> GameResult<RangedAttackTable> {
+        let contents = config_file::read(ctx, RANGED_ATTACKS_FILE)?;
+        RangedAttackTable::parse(&contents)
+    }
+
+    /// Parses lines of the form `attack: key = value, key = value, ...`
+    pub fn parse(contents: &str) -> GameResult<RangedAttackTable> {
+        let mut attacks: BTreeMap<String, ArmedMonster> = BTreeMap::new();
+
+        for line in config_file::lines(
+            RANGED_ATTACKS_FILE,
+            contents,
+            ':',
+            "attack: key = value, ...",
+        )? {
+            let name = line.head;
+            let values: HashMap<&str, &str> = config_file::list(line.rest)
+                .map(config_file::key_value)
+                .collect();
+
+            let value = |key: &str| {
+                values
+                    .get(key)
+                    .copied()
+                    .ok_or_else(|| line.error(format!("`{}` is missing `{}`", name, key)))
+            };
+            let number = |key: &str| {
+                let text = value(key)?;
+                text.parse::<i32>()
+                    .map_err(|_| line.error(format!("`{}` is not a number for `{}`", text, key)))
+            };
+
+            let damage = value("damage")?;
+            let (n_dice, die_type) = parse_dice(damage)
+                .filter(|(n_dice, die_type)| *n_dice >= 1 && *die_type >= 1)
+                .ok_or_else(|| line.error(format!("`{}` is not a dice roll like 1d6", damage)))?;
+            let kind_name = value("kind")?;
+            let kind = DamageType::from_name(kind_name)
+                .ok_or_else(|| line.error(format!("unknown damage type `{}`", kind_name)))?;
+            let monster = value("monster")?;
+            if !MONSTER_TYPES.contains(&monster) {
+                return Err(line.error(format!("unknown monster type `{}`", monster)));
+            }
+
+            attacks.insert(
+                name.to_string(),
+                ArmedMonster {
+                    monster: monster.to_string(),
+                    name: value("monster_name")?.to_string(),
+                    attack: RangedAttack {
+                        range: number("range")?,
+                        keep_away: number("keep_away")?,
+                        n_dice,
+                        die_type,
+                        kind,
+                        verb: value("verb")?.to_string(),
+                        projectile: value("projectile")?.to_string(),
+                    },
+                },
+            );
+        }
+
+        Ok(RangedAttackTable { attacks })
+    }
+
+    /// The attack called `name`, ready to be added to a monster
+    pub fn get(&self, name: &str) -> Option<RangedAttack> {
+        self.attacks.get(name).map(|armed| armed.attack.clone())
+    }
+
+    /// Every armed variant of the `monster` type, possibly none
+    pub fn armed(&self, monster: &str) -> Vec<ArmedMonster> {
+        self.attacks
+            .values()
+            .filter(|armed| armed.monster == monster)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Splits `2d4` into `(2, 4)`
+fn parse_dice(text: &str) -> Option<(i32, i32)> {
+    let mut parts = text.splitn(2, 'd');
+    let n_dice = parts.next()?.trim().parse::<i32>().ok()?;
+    let die_type = parts.next()?.trim().parse::<i32>().ok()?;
+    Some((n_dice, die_type))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_an_attack() {
+        let table = RangedAttackTable::parse(
+            "sling: range = 4, keep_away = 2, damage = 2d3, kind = bludgeoning, \
+             verb = slings, projectile = a stone, monster = goblin, monster_name = Goblin Slinger",
+        )
+        .unwrap();
+        let sling = table.get("sling").unwrap();
+
+        assert_eq!(sling.range, 4);
+        assert_eq!(sling.keep_away, 2);
+        assert_eq!((sling.n_dice, sling.die_type), (2, 3));
+        assert_eq!(sling.kind, DamageType::Bludgeoning);
+        assert_eq!(sling.verb, "slings");
+        assert_eq!(sling.projectile, "a stone");
+        assert!(table.get("bow").is_none());
+    }
+
+    #[test]
+    fn finds_the_armed_variants_of_a_monster() {
+        let table = RangedAttackTable::parse(
+            "sling: range = 4, keep_away = 2, damage = 2d3, kind = bludgeoning, \
+             verb = slings, projectile = a stone, monster = goblin, monster_name = Goblin Slinger",
+        )
+        .unwrap();
+        let goblins = table.armed("goblin");
+
+        assert_eq!(goblins.len(), 1);
+        assert_eq!(goblins[0].name, "Goblin Slinger");
+        assert_eq!(goblins[0].attack.verb, "slings");
+        assert!(table.armed("orc").is_empty());
+    }
+
+    #[test]
+    fn rejects_bad_lines() {
+        let valid = "range = 4, keep_away = 2, damage = 1d6, kind = piercing, verb = shoots, \
+                     projectile = an arrow, monster = goblin, monster_name = Goblin Archer";
+        let bow = |entries: &str| RangedAttackTable::parse(&format!("bow: {}", entries));
+        assert!(bow(valid).is_ok());
+
+        assert!(bow(&valid.replace("range = 4", "range = far")).is_err());
+        assert!(bow(&valid.replace("1d6", "d6")).is_err());
+        assert!(bow(&valid.replace("1d6", "1d0")).is_err());
+        assert!(bow(&valid.replace("1d6", "0d6")).is_err());
+        assert!(bow(&valid.replace("1d6", "1d-3")).is_err());
+        assert!(bow(&valid.replace("piercing", "tickling")).is_err());
+        assert!(bow(&valid.replace("= goblin", "= dragon")).is_err());
+        assert!(bow("range = 4, keep_away = 2, damage = 1d6").is_err());
+    }
+
+    #[test]
+    fn default_ranged_attacks_file_parses() {
+        let table =
+            RangedAttackTable::parse(include_str!("../resources/ranged_attacks.cfg")).unwrap();
+
+        assert!(table.get("bow").is_some());
+        assert!(table.get("firebolt").is_some());
+    }
+}
@@ -0,0 +1,151 @@
+extern crate specs;
+use super::melee_combat_system::{roll_attack, Attack, AttackOutcome, Defenses, COMBAT_NOISE};
+use super::{
+    gamelog::GameLog, Asleep, Attributes, CombatStats, DefenseBonus, Equipped, Name, Noise,
+    RangedAttack, Skills, SufferDamage, WantsToShoot,
+};
+use rltk::RandomNumberGenerator;
+use specs::prelude::*;
+
+pub struct RangedCombatSystem {}
+
+impl<'a> System<'a> for RangedCombatSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteStorage<'a, WantsToShoot>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, CombatStats>,
+        WriteStorage<'a, SufferDamage>,
+        ReadStorage<'a, RangedAttack>,
+        ReadStorage<'a, DefenseBonus>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, Attributes>,
+        WriteStorage<'a, Skills>,
+        WriteStorage<'a, Noise>,
+        ReadStorage<'a, Asleep>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            player_entity,
+            mut log,
+            mut rng,
+            mut wants_shoot,
+            names,
+            combat_stats,
+            mut inflict_damage,
+            ranged_attacks,
+            defense_bonuses,
+            equipped,
+            attributes,
+            mut skills,
+            mut noises,
+            asleep,
+        ) = data;
+
+        for (entity, wants_shoot, name, stats, attack) in (
+            &entities,
+            &wants_shoot,
+            &names,
+            &combat_stats,
+            &ranged_attacks,
+        )
+            .join()
+        {
+            // Neither side can be dead
+            let target_stats = combat_stats.get(wants_shoot.target).unwrap();
+            if stats.hp <= 0 || target_stats.hp <= 0 {
+                continue;
+            }
+            let target_name = names.get(wants_shoot.target).unwrap();
+
+            // Shooting is just as loud as fighting up close, hit or miss
+            noises
+                .insert(
+                    entity,
+                    Noise {
+                        volume: COMBAT_NOISE,
+                    },
+                )
+                .expect("Unable to insert noise");
+
+            // Aim comes down to skill and quickness
+            let quickness_bonus = attributes.get(entity).map_or(0, |a| a.quickness_bonus());
+            let ranged_skill = skills.get(entity).map_or(0, |s| s.ranged.level);
+
+            let shot = Attack {
+                attacker: entity,
+                target: wants_shoot.target,
+                to_hit: ranged_skill + quickness_bonus,
+                n_dice: attack.n_dice,
+                die_type: attack.die_type,
+                damage_bonus: 0,
+                skill: ("ranged", |s| &mut s.ranged),
+            };
+            let defenses = Defenses {
+                combat_stats: &combat_stats,
+                attributes: &attributes,
+                defense_bonuses: &defense_bonuses,
+                equipped: &equipped,
+                asleep: &asleep,
+            };
+            let outcome = roll_attack(
+                &shot,
+                &defenses,
+                &mut skills,
+                &mut rng,
+                &mut log,
+                *player_entity,
+            );
+
+            let (damage, critical, sneak_attack) = match outcome {
+                AttackOutcome::Fumble | AttackOutcome::Miss => {
+                    log.entries.push(format!(
+                        "{} {} {} at {}, but misses.\n",
+                        &name.name, attack.verb, attack.projectile, &target_name.name
+                    ));
+                    continue;
+                }
+                AttackOutcome::Hit {
+                    damage,
+                    critical,
+                    sneak_attack,
+                } => (damage, critical, sneak_attack),
+            };
+
+            if damage == 0 {
+                log.entries.push(format!(
+                    "{} {} {} at {}, but it doesn't hurt them.\n",
+                    &name.name, attack.verb, attack.projectile, &target_name.name
+                ));
+            } else {
+                // How much it actually hurts is logged by the damage system
+                let hit = if sneak_attack {
+                    "hits them in their sleep"
+                } else if critical {
+                    "critically hits"
+                } else {
+                    "hits"
+                };
+                log.entries.push(format!(
+                    "{} {} {} at {}, and {}.\n",
+                    &name.name, attack.verb, attack.projectile, &target_name.name, hit
+                ));
+                SufferDamage::new_damage(
+                    &mut inflict_damage,
+                    wants_shoot.target,
+                    damage,
+                    attack.kind,
+                    Some(entity),
+                );
+            }
+        }
+        // Every shot has been taken, so clear them
+        wants_shoot.clear();
+    }
+}
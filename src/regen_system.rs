@@ -0,0 +1,64 @@
+extern crate specs;
+use super::{CombatStats, Follower, HealthRegen, Map, Monster, Player, Viewshed};
+use specs::prelude::*;
+
+extern crate rltk;
+
+pub struct RegenSystem {}
+
+impl<'a> System<'a> for RegenSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        ReadExpect<'a, Map>,
+        ReadExpect<'a, rltk::Point>, // Player pos
+        Entities<'a>,
+        WriteStorage<'a, HealthRegen>,
+        WriteStorage<'a, CombatStats>,
+        ReadStorage<'a, Viewshed>,
+        ReadStorage<'a, Player>,
+        ReadStorage<'a, Monster>,
+        ReadStorage<'a, Follower>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            map,
+            player_pos,
+            entities,
+            mut regens,
+            mut combat_stats,
+            viewsheds,
+            players,
+            monsters,
+            followers,
+        ) = data;
+
+        for (entity, regen, stats, viewshed) in
+            (&entities, &mut regens, &mut combat_stats, &viewsheds).join()
+        {
+            // No regeneration while enemies are visible
+            let on_players_side = players.get(entity).is_some() || followers.get(entity).is_some();
+            let enemy_visible = if on_players_side {
+                viewshed.visible_tiles.iter().any(|tile| {
+                    let idx = map.xy_idx(tile.x, tile.y);
+                    map.tile_content[idx]
+                        .iter()
+                        .any(|e| monsters.get(*e).is_some() && followers.get(*e).is_none())
+                })
+            } else {
+                viewshed.visible_tiles.contains(&*player_pos)
+            };
+
+            if enemy_visible || stats.hp >= stats.max_hp {
+                regen.counter = 0;
+                continue;
+            }
+
+            regen.counter += 1;
+            if regen.counter >= regen.turns_per_hp {
+                regen.counter = 0;
+                stats.heal(1);
+            }
+        }
+    }
+}
@@ -0,0 +1,39 @@
+extern crate specs;
+use super::{GridPosition, Map, Player};
+use specs::prelude::*;
+
+/// How strong a fresh scent is, which is also how many turns it takes to fade away
+pub const PLAYER_SCENT: i32 = 50;
+
+/// Lays down a fresh scent wherever the player is, so even a hasted player leaves a trail
+pub struct ScentSystem {}
+
+impl<'a> System<'a> for ScentSystem {
+    type SystemData = (
+        WriteExpect<'a, Map>,
+        ReadStorage<'a, GridPosition>,
+        ReadStorage<'a, Player>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (mut map, positions, players) = data;
+
+        for (pos, _player) in (&positions, &players).join() {
+            let idx = map.xy_idx(pos.x, pos.y);
+            map.scent[idx] = PLAYER_SCENT;
+        }
+    }
+}
+
+/// Fades every scent on the map by one turn's worth
+pub struct ScentFadeSystem {}
+
+impl<'a> System<'a> for ScentFadeSystem {
+    type SystemData = WriteExpect<'a, Map>;
+
+    fn run(&mut self, mut map: Self::SystemData) {
+        for scent in map.scent.iter_mut() {
+            *scent = i32::max(0, *scent - 1);
+        }
+    }
+}
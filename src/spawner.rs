@@ -0,0 +1,676 @@
+extern crate specs;
+use specs::prelude::*;
+
+use ggez::graphics as gfx;
+
+extern crate rltk;
+use rltk::RandomNumberGenerator;
+
+use super::hunger_system::WELL_FED_DURATION;
+use super::initiative_system::NORMAL_SPEED;
+use super::pack_system::Packs;
+use super::ranged_attacks::RangedAttackTable;
+use super::{
+    AiState, AreaOfEffect, Asleep, Attributes, BlocksTile, Brain, CombatStats, Consumable, Corpse,
+    DamageType, DefenseBonus, Energy, EquipmentSlot, Equippable, Experience, Faction, Follower,
+    GrantsXp, GridPosition, HealthRegen, HungerClock, HungerState, InflictsDamage, InflictsStatus,
+    Item, LootEntry, LootTable, MeleeDamage, MeleePowerBonus, Monster, Name, PackMember, Player,
+    ProvidesFood, ProvidesHealing, Ranged, RangedAttack, Rect32, Renderable, Resistant, Skill,
+    Skills, StatusEffectKind, Tracker, Viewshed, Vulnerable,
+};
+
+const MAX_ITEMS: i32 = 2;
+
+/// The monster types data files can refer to
+pub const MONSTER_TYPES: [&str; 2] = ["goblin", "orc"];
+
+/// Spawns the player and returns their entity object
+pub fn player(ecs: &mut World, player_x: i32, player_y: i32) -> Entity {
+    ecs.create_entity()
+        .with(GridPosition {
+            x: player_x,
+            y: player_y,
+        })
+        .with(Renderable {
+            color: gfx::Color::new(0., 1., 0., 1.),
+            render_order: 0,
+        })
+        .with(Player {})
+        // So monsters stumbling or sniffing around don't walk onto the player
+        .with(BlocksTile {})
+        .with(Viewshed {
+            visible_tiles: Vec::new(),
+            range: 8,
+            dirty: true,
+        })
+        .with(Name {
+            name: "Player".to_string(),
+        })
+        .with(CombatStats {
+            max_hp: 30,
+            hp: 30,
+            defense: 2,
+            power: 5,
+        })
+        .with(HealthRegen {
+            turns_per_hp: 5,
+            counter: 0,
+        })
+        .with(HungerClock {
+            state: HungerState::WellFed,
+            duration: WELL_FED_DURATION,
+        })
+        .with(Experience { level: 1, xp: 0 })
+        .with(Attributes {
+            might: 12,
+            fitness: 12,
+            quickness: 11,
+            intelligence: 11,
+        })
+        .with(Skills {
+            melee: Skill::new(1),
+            ranged: Skill::new(0),
+            defense: Skill::new(1),
+            magic: Skill::new(1),
+        })
+        // Fists
+        .with(MeleeDamage {
+            n_dice: 1,
+            die_type: 4,
+            kind: DamageType::Bludgeoning,
+        })
+        .with(Energy {
+            speed: NORMAL_SPEED,
+            current: 0,
+        })
+        .with(Faction {
+            name: "player".to_string(),
+        })
+        .build()
+}
+
+/// Spawns a dog that follows the player around and fights on their side
+pub fn companion(ecs: &mut World, x: i32, y: i32) -> Entity {
+    ecs.create_entity()
+        .with(GridPosition { x, y })
+        .with(Renderable {
+            color: gfx::Color::new(0.6, 0.4, 0.2, 1.0),
+            render_order: 1,
+        })
+        .with(Viewshed {
+            visible_tiles: Vec::new(),
+            range: 8,
+            dirty: true,
+        })
+        .with(Monster {})
+        .with(Brain {
+            state: AiState::Follow,
+            flee_below: 25,
+            last_seen: None,
+        })
+        .with(Follower { leash: 6 })
+        .with(Name {
+            name: "Dog".to_string(),
+        })
+        .with(BlocksTile {})
+        .with(CombatStats {
+            max_hp: 14,
+            hp: 14,
+            defense: 0,
+            power: 2,
+        })
+        .with(HealthRegen {
+            turns_per_hp: 5,
+            counter: 0,
+        })
+        .with(Attributes {
+            might: 10,
+            fitness: 10,
+            quickness: 13,
+            intelligence: 4,
+        })
+        .with(Skills {
+            melee: Skill::new(1),
+            ranged: Skill::new(0),
+            defense: Skill::new(1),
+            magic: Skill::new(0),
+        })
+        .with(MeleeDamage {
+            n_dice: 1,
+            die_type: 6,
+            kind: DamageType::Piercing,
+        })
+        .with(Energy {
+            speed: NORMAL_SPEED + 2,
+            current: 0,
+        })
+        .with(Faction {
+            name: "player".to_string(),
+        })
+        .build()
+}
+
+/// Fills a room: a monster or a pack of goblins in the center and a few items scattered around
+pub fn spawn_room(ecs: &mut World, room: &Rect32, i: usize) {
+    let (x, y) = room.center();
+    let pack = ecs
+        .write_resource::<RandomNumberGenerator>()
+        .roll_dice(1, 4)
+        == 1;
+    let monster_spots = if pack {
+        goblin_pack(ecs, x, y, i)
+    } else {
+        random_monster(ecs, x, y, i);
+        vec![(x, y)]
+    };
+
+    let mut item_spawn_points: Vec<(i32, i32)> = Vec::new();
+    {
+        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+        let num_items = rng.roll_dice(1, MAX_ITEMS + 3) - 3;
+
+        for _ in 0..num_items {
+            let mut added = false;
+            while !added {
+                let item_x = room.x1 + 1 + rng.roll_dice(1, room.x2 - room.x1) - 1;
+                let item_y = room.y1 + 1 + rng.roll_dice(1, room.y2 - room.y1) - 1;
+
+                // Not under a monster and not on top of another item
+                let spot = (item_x, item_y);
+                if !monster_spots.contains(&spot) && !item_spawn_points.contains(&spot) {
+                    item_spawn_points.push(spot);
+                    added = true;
+                }
+            }
+        }
+    }
+
+    for (item_x, item_y) in item_spawn_points.iter() {
+        random_item(ecs, *item_x, *item_y);
+    }
+}
+
+/// Spawns a random monster at a given location. `i` is only used to number their names.
+/// Some of them may be armed with a ranged attack, and about a third start off asleep.
+pub fn random_monster(ecs: &mut World, x: i32, y: i32, i: usize) {
+    let roll: i32;
+    let asleep: bool;
+    {
+        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+        roll = rng.roll_dice(1, 6);
+        asleep = rng.roll_dice(1, 3) == 1;
+    }
+
+    let entity = match roll {
+        1 | 2 => goblin(ecs, x, y, "Goblin", i),
+        3 => {
+            armed_monster(ecs, "goblin", x, y, i).unwrap_or_else(|| goblin(ecs, x, y, "Goblin", i))
+        }
+        4 | 5 => orc(ecs, x, y, "Orc", i),
+        _ => armed_monster(ecs, "orc", x, y, i).unwrap_or_else(|| orc(ecs, x, y, "Orc", i)),
+    };
+
+    if asleep {
+        ecs.write_storage::<Asleep>()
+            .insert(entity, Asleep { saw_player: false })
+            .expect("Unable to insert Asleep");
+    }
+}
+
+/// A goblin boss with a few goblins around it, returning where they all stand.
+/// The rest of the pack may run for it if the boss dies.
+fn goblin_pack(ecs: &mut World, x: i32, y: i32, i: usize) -> Vec<(i32, i32)> {
+    let spots = vec![(x, y), (x - 1, y), (x + 1, y), (x, y + 1)];
+
+    let leader = goblin(ecs, x, y, "Goblin Boss", i);
+    {
+        // Bosses are a bit tougher than the rest
+        let mut stats = ecs.write_storage::<CombatStats>();
+        if let Some(stats) = stats.get_mut(leader) {
+            stats.max_hp += 8;
+            stats.hp += 8;
+        }
+    }
+    let leader_name = ecs
+        .read_storage::<Name>()
+        .get(leader)
+        .map_or_else(String::new, |name| name.name.clone());
+    let pack = ecs.write_resource::<Packs>().new_pack(leader, leader_name);
+
+    let mut pack_members = vec![leader];
+    for (member_x, member_y) in spots.iter().skip(1) {
+        pack_members.push(goblin(ecs, *member_x, *member_y, "Goblin", i));
+    }
+    for member in pack_members {
+        ecs.write_storage::<PackMember>()
+            .insert(
+                member,
+                PackMember {
+                    pack,
+                    routed: false,
+                },
+            )
+            .expect("Unable to insert PackMember");
+    }
+
+    spots
+}
+
+/// Spawns one of the variants of `monster_type` that the ranged attacks file arms,
+/// or nothing if it doesn't arm that type at all
+fn armed_monster(ecs: &mut World, monster_type: &str, x: i32, y: i32, i: usize) -> Option<Entity> {
+    let mut variants = ecs.fetch::<RangedAttackTable>().armed(monster_type);
+    if variants.is_empty() {
+        return None;
+    }
+    let pick = ecs
+        .write_resource::<RandomNumberGenerator>()
+        .roll_dice(1, variants.len() as i32);
+    let armed = variants.swap_remove(pick as usize - 1);
+
+    let entity = match monster_type {
+        "goblin" => goblin(ecs, x, y, &armed.name, i),
+        _ => orc(ecs, x, y, &armed.name, i),
+    };
+    ecs.write_storage::<RangedAttack>()
+        .insert(entity, armed.attack)
+        .expect("Unable to insert RangedAttack");
+    Some(entity)
+}
+
+fn goblin(ecs: &mut World, x: i32, y: i32, name: &str, i: usize) -> Entity {
+    // Quick on their feet but weak
+    monster(ecs, x, y, gfx::Color::new(1.0, 0.0, 0.75, 1.0), name, 30, i)
+        .with(Attributes {
+            might: 8,
+            fitness: 10,
+            quickness: 13,
+            intelligence: 8,
+        })
+        .with(Skills {
+            melee: Skill::new(1),
+            ranged: Skill::new(1),
+            defense: Skill::new(2),
+            magic: Skill::new(0),
+        })
+        .with(MeleeDamage {
+            n_dice: 1,
+            die_type: 4,
+            kind: DamageType::Slashing,
+        })
+        .with(Vulnerable {
+            kinds: vec![DamageType::Fire],
+        })
+        .with(Energy {
+            speed: NORMAL_SPEED + 2,
+            current: 0,
+        })
+        .with(Faction {
+            name: "goblins".to_string(),
+        })
+        .with(LootTable {
+            drop_chance: 50,
+            entries: vec![
+                LootEntry {
+                    weight: 3,
+                    spawn: health_potion,
+                },
+                LootEntry {
+                    weight: 2,
+                    spawn: poison_dart,
+                },
+                LootEntry {
+                    weight: 1,
+                    spawn: dagger,
+                },
+            ],
+        })
+        .build()
+}
+
+fn orc(ecs: &mut World, x: i32, y: i32, name: &str, i: usize) -> Entity {
+    // Hits hard, easy to land a blow on, and sniffs out anyone who runs away
+    monster(ecs, x, y, gfx::Color::new(1.0, 0.0, 0.1, 1.0), name, 50, i)
+        .with(Attributes {
+            might: 13,
+            fitness: 12,
+            quickness: 8,
+            intelligence: 7,
+        })
+        .with(Skills {
+            melee: Skill::new(2),
+            ranged: Skill::new(0),
+            defense: Skill::new(1),
+            magic: Skill::new(0),
+        })
+        .with(MeleeDamage {
+            n_dice: 1,
+            die_type: 6,
+            kind: DamageType::Slashing,
+        })
+        .with(Resistant {
+            kinds: vec![DamageType::Poison],
+        })
+        .with(Energy {
+            speed: NORMAL_SPEED - 2,
+            current: 0,
+        })
+        .with(Faction {
+            name: "orcs".to_string(),
+        })
+        .with(Tracker {})
+        .with(LootTable {
+            drop_chance: 60,
+            entries: vec![
+                LootEntry {
+                    weight: 2,
+                    spawn: rations,
+                },
+                LootEntry {
+                    weight: 2,
+                    spawn: health_potion,
+                },
+                LootEntry {
+                    weight: 1,
+                    spawn: shield,
+                },
+                LootEntry {
+                    weight: 1,
+                    spawn: leather_armor,
+                },
+            ],
+        })
+        .build()
+}
+
+/// Everything monsters have in common. Callers add their own attributes and skills, then build.
+fn monster<S: ToString>(
+    ecs: &mut World,
+    x: i32,
+    y: i32,
+    color: gfx::Color,
+    name: S,
+    xp: i32,
+    i: usize,
+) -> EntityBuilder<'_> {
+    ecs.create_entity()
+        .with(GridPosition { x, y })
+        .with(Renderable {
+            color,
+            render_order: 1,
+        })
+        .with(Viewshed {
+            visible_tiles: Vec::new(),
+            range: 8,
+            dirty: true,
+        })
+        .with(Monster {})
+        .with(Brain {
+            state: AiState::Wander,
+            flee_below: 25,
+            last_seen: None,
+        })
+        .with(Name {
+            name: format!("{} #{}", name.to_string(), i),
+        })
+        .with(BlocksTile {})
+        .with(CombatStats {
+            max_hp: 16,
+            hp: 16,
+            defense: 1,
+            power: 4,
+        })
+        .with(GrantsXp { xp })
+}
+
+/// What's left of `name` after they die. Drawn dimmed, under any items.
+pub fn corpse(ecs: &mut World, x: i32, y: i32, name: &str, color: gfx::Color) {
+    ecs.create_entity()
+        .with(GridPosition { x, y })
+        .with(Renderable {
+            color: gfx::Color::new(color.r * 0.4, color.g * 0.4, color.b * 0.4, 1.0),
+            render_order: 3,
+        })
+        .with(Name {
+            name: format!("{} corpse", name),
+        })
+        .with(Corpse {})
+        .build();
+}
+
+fn random_item(ecs: &mut World, x: i32, y: i32) {
+    let roll: i32;
+    {
+        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+        roll = rng.roll_dice(1, 13);
+    }
+
+    match roll {
+        1 | 2 => health_potion(ecs, x, y),
+        10 | 11 => rations(ecs, x, y),
+        3 => fireball_scroll(ecs, x, y),
+        4 => dagger(ecs, x, y),
+        5 => shield(ecs, x, y),
+        6 => leather_armor(ecs, x, y),
+        7 => confusion_scroll(ecs, x, y),
+        8 => poison_dart(ecs, x, y),
+        9 => stun_scroll(ecs, x, y),
+        12 => haste_potion(ecs, x, y),
+        _ => magic_missile_scroll(ecs, x, y),
+    }
+}
+
+fn health_potion(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(GridPosition { x, y })
+        .with(Renderable {
+            color: gfx::Color::new(0.0, 0.8, 1.0, 1.0),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Health Potion".to_string(),
+        })
+        .with(Item {})
+        .with(Consumable {})
+        .with(ProvidesHealing { heal_amount: 8 })
+        .build();
+}
+
+fn magic_missile_scroll(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(GridPosition { x, y })
+        .with(Renderable {
+            color: gfx::Color::new(1.0, 1.0, 0.0, 1.0),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Magic Missile Scroll".to_string(),
+        })
+        .with(Item {})
+        .with(Consumable {})
+        .with(Ranged { range: 6 })
+        .with(InflictsDamage {
+            damage: 8,
+            kind: DamageType::Magic,
+        })
+        .build();
+}
+
+fn fireball_scroll(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(GridPosition { x, y })
+        .with(Renderable {
+            color: gfx::Color::new(1.0, 0.5, 0.0, 1.0),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Fireball Scroll".to_string(),
+        })
+        .with(Item {})
+        .with(Consumable {})
+        .with(Ranged { range: 6 })
+        .with(InflictsDamage {
+            damage: 20,
+            kind: DamageType::Fire,
+        })
+        .with(AreaOfEffect { radius: 3 })
+        .build();
+}
+
+fn dagger(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(GridPosition { x, y })
+        .with(Renderable {
+            color: gfx::Color::new(0.0, 1.0, 1.0, 1.0),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Dagger".to_string(),
+        })
+        .with(Item {})
+        .with(Equippable {
+            slot: EquipmentSlot::Melee,
+        })
+        .with(MeleeDamage {
+            n_dice: 1,
+            die_type: 6,
+            kind: DamageType::Piercing,
+        })
+        .with(MeleePowerBonus { power: 1 })
+        .build();
+}
+
+fn shield(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(GridPosition { x, y })
+        .with(Renderable {
+            color: gfx::Color::new(0.0, 1.0, 1.0, 1.0),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Shield".to_string(),
+        })
+        .with(Item {})
+        .with(Equippable {
+            slot: EquipmentSlot::Shield,
+        })
+        .with(DefenseBonus { defense: 1 })
+        .build();
+}
+
+fn leather_armor(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(GridPosition { x, y })
+        .with(Renderable {
+            color: gfx::Color::new(0.0, 1.0, 1.0, 1.0),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Leather Armor".to_string(),
+        })
+        .with(Item {})
+        .with(Equippable {
+            slot: EquipmentSlot::Armor,
+        })
+        .with(DefenseBonus { defense: 1 })
+        .build();
+}
+
+fn confusion_scroll(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(GridPosition { x, y })
+        .with(Renderable {
+            color: gfx::Color::new(1.0, 0.5, 1.0, 1.0),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Confusion Scroll".to_string(),
+        })
+        .with(Item {})
+        .with(Consumable {})
+        .with(Ranged { range: 6 })
+        .with(InflictsStatus {
+            kind: StatusEffectKind::Confusion,
+            turns: 4,
+        })
+        .build();
+}
+
+fn poison_dart(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(GridPosition { x, y })
+        .with(Renderable {
+            color: gfx::Color::new(0.5, 1.0, 0.0, 1.0),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Poison Dart".to_string(),
+        })
+        .with(Item {})
+        .with(Consumable {})
+        .with(Ranged { range: 5 })
+        .with(InflictsDamage {
+            damage: 1,
+            kind: DamageType::Piercing,
+        })
+        .with(InflictsStatus {
+            kind: StatusEffectKind::Poison { damage: 2 },
+            turns: 5,
+        })
+        .build();
+}
+
+fn stun_scroll(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(GridPosition { x, y })
+        .with(Renderable {
+            color: gfx::Color::new(1.0, 1.0, 1.0, 1.0),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Stun Scroll".to_string(),
+        })
+        .with(Item {})
+        .with(Consumable {})
+        .with(Ranged { range: 6 })
+        .with(InflictsStatus {
+            kind: StatusEffectKind::Stun,
+            turns: 2,
+        })
+        .build();
+}
+
+fn rations(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(GridPosition { x, y })
+        .with(Renderable {
+            color: gfx::Color::new(0.6, 0.4, 0.2, 1.0),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Rations".to_string(),
+        })
+        .with(Item {})
+        .with(Consumable {})
+        .with(ProvidesFood {})
+        .build();
+}
+
+fn haste_potion(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(GridPosition { x, y })
+        .with(Renderable {
+            color: gfx::Color::new(1.0, 1.0, 0.0, 1.0),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Potion of Haste".to_string(),
+        })
+        .with(Item {})
+        .with(Consumable {})
+        .with(InflictsStatus {
+            kind: StatusEffectKind::Haste,
+            turns: 10,
+        })
+        .build();
+}
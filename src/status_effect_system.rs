@@ -0,0 +1,45 @@
+extern crate specs;
+use super::{gamelog::GameLog, DamageType, Name, StatusEffectKind, StatusEffects, SufferDamage};
+use specs::prelude::*;
+
+pub struct StatusEffectSystem {}
+
+impl<'a> System<'a> for StatusEffectSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        WriteExpect<'a, GameLog>,
+        Entities<'a>,
+        WriteStorage<'a, StatusEffects>,
+        ReadStorage<'a, Name>,
+        WriteStorage<'a, SufferDamage>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (mut gamelog, entities, mut status_effects, names, mut suffer_damage) = data;
+
+        for (entity, status, name) in (&entities, &mut status_effects, &names).join() {
+            for effect in status.effects.iter_mut() {
+                if let StatusEffectKind::Poison { damage } = effect.kind {
+                    SufferDamage::new_damage(
+                        &mut suffer_damage,
+                        entity,
+                        damage,
+                        DamageType::Poison,
+                        effect.source,
+                    );
+                }
+
+                effect.turns -= 1;
+                if effect.turns < 1 {
+                    gamelog.entries.push(format!(
+                        "{} is no longer {}.",
+                        name.name,
+                        effect.kind.adjective()
+                    ));
+                }
+            }
+
+            status.effects.retain(|e| e.turns > 0);
+        }
+    }
+}
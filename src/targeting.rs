@@ -0,0 +1,83 @@
+use ggez::event::KeyCode;
+use ggez::input::mouse;
+use ggez::Context;
+
+use specs::prelude::*;
+
+extern crate rltk;
+use rltk::Point;
+
+use super::map::GRID_TILE_SIZE;
+use super::{Map, State, Viewshed};
+
+pub enum TargetingResult {
+    Cancel,
+    NoResponse { cursor: Point },
+    Selected(Point),
+}
+
+/// Tiles the player can see that are within `range` of them, not counting their own
+pub fn valid_targets(ecs: &World, range: i32) -> Vec<Point> {
+    let player_entity = ecs.fetch::<Entity>();
+    let player_pos = ecs.fetch::<Point>();
+    let viewsheds = ecs.read_storage::<Viewshed>();
+
+    match viewsheds.get(*player_entity) {
+        None => Vec::new(),
+        Some(viewshed) => viewshed
+            .visible_tiles
+            .iter()
+            .filter(|tile| {
+                **tile != *player_pos
+                    && rltk::DistanceAlg::Pythagoras.distance2d(*player_pos, **tile) <= range as f32
+            })
+            .cloned()
+            .collect(),
+    }
+}
+
+/// Moves the target cursor with the movement keys or the mouse.
+/// Enter or a click confirms a valid tile, Escape cancels.
+pub fn targeting_input(gs: &State, ctx: &Context, range: i32, cursor: Point) -> TargetingResult {
+    let targets = valid_targets(&gs.ecs, range);
+    let mut cursor = cursor;
+
+    // Mouse picks the tile under it
+    if gs.mouse_moved || gs.mouse_clicked {
+        let mouse_pos = mouse::position(ctx);
+        let map = gs.ecs.fetch::<Map>();
+        let tile_x = mouse_pos.x as i32 / GRID_TILE_SIZE;
+        let tile_y = mouse_pos.y as i32 / GRID_TILE_SIZE;
+        if tile_x >= 0 && tile_x < map.width && tile_y >= 0 && tile_y < map.height {
+            cursor = Point::new(tile_x, tile_y);
+        }
+
+        if gs.mouse_clicked && targets.contains(&cursor) {
+            return TargetingResult::Selected(cursor);
+        }
+    }
+
+    if let Some(key) = gs.key {
+        match key {
+            KeyCode::Escape => return TargetingResult::Cancel,
+            KeyCode::Return | KeyCode::NumpadEnter => {
+                if targets.contains(&cursor) {
+                    return TargetingResult::Selected(cursor);
+                }
+            }
+            _ => {
+                let direction = gs.keybindings.action_for(key).and_then(|a| a.direction());
+                if let Some((delta_x, delta_y)) = direction {
+                    // Keep the cursor on the map
+                    let map = gs.ecs.fetch::<Map>();
+                    cursor = Point::new(
+                        i32::max(0, i32::min(map.width - 1, cursor.x + delta_x)),
+                        i32::max(0, i32::min(map.height - 1, cursor.y + delta_y)),
+                    );
+                }
+            }
+        }
+    }
+
+    TargetingResult::NoResponse { cursor }
+}